@@ -1,13 +1,20 @@
-use rusqlite::{params, Connection};
+use arrow_array::builder::{BinaryBuilder, Float64Builder, Int64Builder, StringBuilder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use rusqlite::{params, params_from_iter, Connection, OpenFlags, OptionalExtension};
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs::OpenOptions;
 use std::io::Write;
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use tauri::{Emitter, Manager, State};
+use tokio::sync::mpsc;
 
 pub mod version;
 
@@ -37,6 +44,32 @@ pub struct DatabaseMetadata {
     created_at: String,
     last_accessed: String,
     analysis_results: Option<String>,
+    is_encrypted: bool,
+    file_exists: bool,
+    icon: Option<String>,  // emoji, icon name or URL, rendered by the frontend
+    notes: Option<String>, // free-form Markdown
+    file_size_kb: Option<u64>, // read from disk, not stored
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportResult {
+    pub metadata: DatabaseMetadata,
+    pub is_duplicate: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportError {
+    pub reason: String,
+    pub message: String,
+}
+
+impl ImportError {
+    fn new(reason: &str, message: String) -> Self {
+        Self {
+            reason: reason.to_string(),
+            message,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -45,200 +78,7051 @@ pub struct AnalysisResults {
     pub type_distribution: TypeDistribution,
     pub char_frequency: HashMap<u32, u64>, // Unicode to count
     pub column_formats: HashMap<String, Vec<String>>, // Table.Column to possible formats
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    #[serde(default)]
+    pub column_type_mismatches: HashMap<String, u64>, // Table.Column to mismatched cell count
+    #[serde(default)]
+    pub column_max_lengths: HashMap<String, usize>, // Table.Column to longest text (chars) or blob (bytes)
+    #[serde(default)]
+    pub column_sample_values: HashMap<String, Vec<serde_json::Value>>, // Table.Column to first distinct values
+    #[serde(default)]
+    pub partial_analysis: Option<PartialAnalysis>, // set while a cancelled analysis can be resumed
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct PartialAnalysis {
+    pub completed_tables: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct TypeDistribution {
+    pub numeric: u64,
+    pub alphabets: u64,
+    pub special: u64,
+    pub unknown: u64,
+}
+
+const COLUMN_SAMPLE_LIMIT: usize = 5;
+
+// Combines partial results, e.g. from analysis workers that scanned different tables
+trait Merge {
+    fn merge(&mut self, other: Self);
+}
+
+impl Merge for TypeDistribution {
+    fn merge(&mut self, other: Self) {
+        self.numeric += other.numeric;
+        self.alphabets += other.alphabets;
+        self.special += other.special;
+        self.unknown += other.unknown;
+    }
+}
+
+impl Merge for AnalysisResults {
+    fn merge(&mut self, other: Self) {
+        self.total_chars += other.total_chars;
+        self.type_distribution.merge(other.type_distribution);
+        for (c, count) in other.char_frequency {
+            *self.char_frequency.entry(c).or_insert(0) += count;
+        }
+        for (key, formats) in other.column_formats {
+            let existing = self.column_formats.entry(key).or_default();
+            for format in formats {
+                if !existing.contains(&format) {
+                    existing.push(format);
+                }
+            }
+        }
+        self.warnings.extend(other.warnings);
+        for (key, count) in other.column_type_mismatches {
+            *self.column_type_mismatches.entry(key).or_insert(0) += count;
+        }
+        for (key, length) in other.column_max_lengths {
+            let existing = self.column_max_lengths.entry(key).or_insert(0);
+            *existing = (*existing).max(length);
+        }
+        if let Some(other_partial) = other.partial_analysis {
+            self.partial_analysis
+                .get_or_insert_with(PartialAnalysis::default)
+                .completed_tables
+                .extend(other_partial.completed_tables);
+        }
+        for (key, values) in other.column_sample_values {
+            let samples = self.column_sample_values.entry(key).or_default();
+            for value in values {
+                if samples.len() >= COLUMN_SAMPLE_LIMIT {
+                    break;
+                }
+                if !samples.contains(&value) {
+                    samples.push(value);
+                }
+            }
+        }
+    }
+}
+
+impl std::ops::Add for AnalysisResults {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self.merge(other);
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnalysisProgress {
+    pub db_path: String,
+    pub progress: f64,
+    pub records_processed: u64,
+    pub total_records: u64,
+    pub time_remaining_secs: u64,
+    pub speed_records_per_sec: f64,
+    pub is_finished: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduledAnalysis {
+    pub db_path: String,
+    pub interval_hours: u64,
+    pub created_at: String,
+    pub last_run_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BatchImportProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TableRowBatch {
+    pub stream_id: String,
+    pub batch_index: u64,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    pub is_last: bool,
+}
+
+const DEFAULT_ANALYSIS_CHUNK_SIZE: u64 = 100;
+const DEFAULT_MAX_PAGE_SIZE: i64 = 10_000;
+const DEFAULT_ANALYSIS_MAX_PARALLELISM: usize = 4;
+const DEFAULT_ROW_COUNT_CACHE_TTL_SECS: u64 = 60;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AppPreferences {
+    pub analysis_chunk_size: u64,
+    pub analysis_max_parallelism: usize, // tables scanned at the same time
+    pub max_page_size: i64,
+    pub theme: String, // "system", "light" or "dark"
+    pub default_page_size: i64,
+    pub default_sort_direction: String, // "asc" or "desc"
+    pub row_count_cache_ttl_secs: u64,
+}
+
+impl Default for AppPreferences {
+    fn default() -> Self {
+        Self {
+            analysis_chunk_size: DEFAULT_ANALYSIS_CHUNK_SIZE,
+            analysis_max_parallelism: DEFAULT_ANALYSIS_MAX_PARALLELISM,
+            max_page_size: DEFAULT_MAX_PAGE_SIZE,
+            theme: "system".to_string(),
+            default_page_size: 10,
+            default_sort_direction: "asc".to_string(),
+            row_count_cache_ttl_secs: DEFAULT_ROW_COUNT_CACHE_TTL_SECS,
+        }
+    }
+}
+
+pub struct AppState {
+    pub metadata_db_path: PathBuf,
+    pub analysis_tasks: Mutex<HashMap<String, Arc<AtomicBool>>>, // db_path to cancellation token
+    pub wal_checkpoints: Mutex<HashMap<String, i32>>, // db_path to busy flag of the last checkpoint
+    pub scheduled_analyses: Mutex<HashMap<String, Arc<AtomicBool>>>, // db_path to cancellation token
+    pub preferences: Mutex<AppPreferences>, // cached copy of the preferences row
+    pub row_streams: Mutex<HashMap<String, Arc<AtomicBool>>>, // stream_id to cancellation token
+    pub active_queries: Mutex<HashMap<String, Box<dyn Fn() + Send>>>, // query_id to interrupt
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AppDataUsage {
+    pub metadata_db_size_bytes: u64,
+    pub databases_tracked: u32,
+    pub databases_with_analysis: u32,
+    pub total_analysis_json_bytes: u64,
+    pub oldest_entry_date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShrinkResult {
+    pub size_before_bytes: u64,
+    pub size_after_bytes: u64,
+    pub saved_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchHistoryEntry {
+    pub id: i64,
+    pub db_path: String,
+    pub table_name: String,
+    pub search_text: String,
+    pub searched_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VersionHistoryEntry {
+    pub id: i64,
+    pub version: String,
+    pub first_seen_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatabaseFilter {
+    pub name_prefix: Option<String>,
+    pub tag: Option<String>,
+    pub accessed_after: Option<String>,
+    pub accessed_before: Option<String>,
+    pub has_analysis: Option<bool>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportEntry {
+    pub name: String,
+    pub path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableInfo {
+    pub name: String,
+    pub row_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ColumnInfo {
+    pub name: String,
+    pub declared_type: String,
+    pub not_null: bool,
+    pub default_value: Option<String>,
+    pub primary_key: i32, // 1-based position in the primary key, 0 if not part of it
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VirtualTableInfo {
+    pub name: String,
+    pub module: String,
+    pub args: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FtsResult {
+    pub rows: Vec<HashMap<String, serde_json::Value>>,
+    pub total_matches: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidationResult {
+    pub is_valid: bool,
+    pub error_message: Option<String>,
+    pub error_offset: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SqliteStat1Row {
+    pub tbl: String,
+    pub idx: String,
+    pub stat: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SqliteStat4Row {
+    pub tbl: String,
+    pub idx: String,
+    pub neq: String,
+    pub nlt: String,
+    pub ndlt: String,
+    pub sample: Vec<u8>, // index key in SQLite's record format
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CrossTableSearchResult {
+    pub table_name: String,
+    pub matching_rows: Vec<Vec<serde_json::Value>>,
+    pub column_names: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ColumnLocation {
+    pub table_name: String,
+    pub column_name: String,
+    pub declared_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AutoIncrementState {
+    pub table_name: String,
+    pub max_seq: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RowidRange {
+    pub min_rowid: Option<i64>,
+    pub max_rowid: Option<i64>,
+    pub row_count: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncodingIssue {
+    pub row_rowid: i64,
+    pub column: String,
+    pub byte_offset: usize, // offset of the first invalid byte in the value
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalInfo {
+    pub wal_exists: bool,
+    pub wal_size_bytes: Option<u64>,
+    pub wal_pages: Option<u64>,
+    pub last_checkpoint_result: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalCheckpointStats {
+    pub timestamp: String,
+    pub busy: i32,
+    pub log: i32,
+    pub checkpointed: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RowChange {
+    pub key_value: serde_json::Value,
+    pub old_values: Vec<serde_json::Value>,
+    pub new_values: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableDiff {
+    pub columns: Vec<String>,
+    pub rows_only_in_a: Vec<Vec<serde_json::Value>>,
+    pub rows_only_in_b: Vec<Vec<serde_json::Value>>,
+    pub rows_changed: Vec<RowChange>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DbStats {
+    pub total_tables: usize,
+    pub total_records: i64,
+    pub file_size_kb: u64,
+    pub page_size: Option<u32>,
+    pub fragmentation_percent: Option<f64>,
+    pub encoding: String,
+    pub journal_mode: String,
+}
+
+// Everything get_table_data can be asked to do besides naming the table. Pages
+// are addressed by page (OFFSET), keyset_pagination or cursor, at most one of
+// the last two at a time; page defaults to 1 and is ignored when a cursor is given.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableQuery {
+    pub page: Option<i64>,
+    pub page_size: i64,
+    pub search: Option<String>,
+    pub datetime_filter: Option<DatetimeFilter>,
+    pub filter_in: Option<FilterIn>,
+    pub reverse: Option<bool>,
+    pub hide_rowid: Option<bool>,
+    pub distinct: Option<bool>,
+    pub keyset_pagination: Option<KeysetParams>,
+    pub cursor: Option<String>,
+    pub include_type_info: Option<bool>,
+    pub change_detection_token: Option<String>,
+    pub timeout_ms: Option<u64>,
+    pub query_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatetimeFilter {
+    pub column: String,
+    pub after: Option<String>,
+    pub before: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FilterIn {
+    pub column: String,
+    pub values: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KeysetParams {
+    pub column: String,
+    pub last_value: serde_json::Value,
+    pub direction: String,
+}
+
+// Decoded form of the opaque cursor tokens handed to the frontend
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RowCursor {
+    pub rowid: i64,
+    pub backward: bool,
+}
+
+impl RowCursor {
+    fn encode(&self) -> String {
+        use base64::Engine;
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    fn decode(token: &str) -> Result<Self, String> {
+        use base64::Engine;
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(token)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| "Invalid pagination cursor".to_string())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AggregateSpec {
+    pub function: String,
+    pub column: String,
+    pub alias: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PivotResult {
+    pub row_headers: Vec<serde_json::Value>,
+    pub col_headers: Vec<serde_json::Value>,
+    pub cells: Vec<Vec<Option<f64>>>, // cells[row][col], None when there's no numeric result
+    pub truncated: bool,              // more than 50 distinct row or column values
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FunctionInfo {
+    pub name: String,
+    pub builtin: bool,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub enc: String,
+    pub narg: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default, Clone)]
-pub struct TypeDistribution {
-    pub numeric: u64,
-    pub alphabets: u64,
-    pub special: u64,
-    pub unknown: u64,
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MigrationHint {
+    pub severity: String,
+    pub category: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DialectReport {
+    pub detected_patterns: Vec<String>,
+    pub compatibility_warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrphanedRow {
+    pub rowid: i64,
+    pub parent_table: String,
+    pub fk_id: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistogramBucket {
+    pub low: f64,
+    pub high: f64,
+    pub count: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DateRangeInfo {
+    pub min_date: Option<String>,
+    pub max_date: Option<String>,
+    pub span_days: Option<f64>,
+    pub null_count: u64,
+    pub detected_format: String, // "ISO 8601", "YYYYMMDD", "Unix timestamp" or "Unknown"
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CorrelationResult {
+    pub pearson_r: Option<f64>,
+    pub n_pairs: u64,
+    pub col_a_mean: f64,
+    pub col_b_mean: f64,
+    pub col_a_std: f64,
+    pub col_b_std: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EntropyResult {
+    pub shannon_entropy: f64, // bits
+    pub distinct_count: u64,
+    pub total_count: u64,
+    pub max_possible_entropy: f64,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConversionReport {
+    pub rows_converted: u64,
+    pub rows_with_null_after_coerce: u64,
+    pub rows_failed: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExpressionIndexInfo {
+    pub index_name: String,
+    pub table_name: String,
+    pub expression: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergeResult {
+    pub tables_merged: u32,
+    pub rows_inserted: u64,
+    pub rows_skipped: u64,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatabaseCapabilities {
+    pub can_read: bool,
+    pub can_write: bool,
+    pub can_create_table: bool,
+    pub is_wal_mode: bool,
+    pub sqlite_version: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConnectionStatus {
+    pub can_open: bool,
+    pub sqlite_version: String,
+    pub journal_mode: String,
+    pub page_size: u32,
+    pub is_wal: bool,
+    pub file_exists: bool,
+    pub file_writable: bool,
+    pub file_size_bytes: u64,
+    pub open_latency_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LockStatus {
+    pub is_locked: bool,
+    pub lock_type: Option<String>, // "shared", "reserved" or "exclusive", as seen from outside
+    pub suggested_wait_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExplainOpcode {
+    pub addr: i64,
+    pub opcode: String,
+    pub p1: i64,
+    pub p2: i64,
+    pub p3: i64,
+    pub p4: Option<String>,
+    pub p5: i64,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TriggerDetails {
+    pub name: String,
+    pub table_name: String,
+    pub timing: String,
+    pub event: String,
+    pub when_condition: Option<String>,
+    pub for_each_row: bool,
+    pub sql: String,
+    pub opcodes: Vec<ExplainOpcode>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConstraintInfo {
+    pub kind: String,
+    pub expression: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryCostEstimate {
+    pub plan_description: String,
+    pub estimated_cost: u64,
+    pub uses_indexes: bool,
+    pub involves_sorting: bool,
+    pub involves_subquery: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IntegritySummary {
+    pub violations_by_table: HashMap<String, u64>,
+    pub total_violations: u64,
+    pub tables_checked: u32,
+    pub tables_with_violations: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableData {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    pub total_pages: i64,
+    pub clamped: bool,
+    pub warnings: Vec<String>,
+    pub has_more: bool,
+    pub row_types: Option<Vec<Vec<String>>>, // storage class per cell, only when requested
+    pub change_token: Option<String>,
+    pub data_changed: bool,
+    pub is_fts: bool,
+    pub snippets: Option<Vec<String>>, // highlighted match per row, only for FTS5 searches
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
+}
+
+fn get_table_columns(conn: &Connection, table: &str) -> Result<Vec<ColumnInfo>, String> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({})", quote_identifier(table)))
+        .map_err(|e| e.to_string())?;
+    let columns = stmt
+        .query_map([], |row| {
+            Ok(ColumnInfo {
+                name: row.get(1)?,
+                declared_type: row.get(2)?,
+                not_null: row.get(3)?,
+                default_value: row.get(4)?,
+                primary_key: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(columns)
+}
+
+// Resolves a declared column type to its SQLite affinity using the rules from
+// https://www.sqlite.org/datatype3.html#determination_of_column_affinity
+fn column_affinity(declared_type: &str) -> &'static str {
+    let t = declared_type.to_uppercase();
+    if t.contains("INT") {
+        "INTEGER"
+    } else if t.contains("CHAR") || t.contains("CLOB") || t.contains("TEXT") {
+        "TEXT"
+    } else if t.is_empty() || t.contains("BLOB") {
+        "BLOB"
+    } else if t.contains("REAL") || t.contains("FLOA") || t.contains("DOUB") {
+        "REAL"
+    } else {
+        "NUMERIC"
+    }
+}
+
+// Quotes an identifier per SQLite rules: wrap in double quotes and double any
+// embedded double quotes, so names like `my "odd" table` stay a single token.
+pub fn quote_identifier(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+fn get_metadata_conn(state: &AppState) -> Result<Connection, String> {
+    Connection::open(&state.metadata_db_path).map_err(|e| e.to_string())
+}
+
+fn migrate_metadata_db(conn: &Connection) -> Result<(), String> {
+    // Create table if not exists
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS metadata (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            path TEXT NOT NULL UNIQUE,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            last_accessed DATETIME DEFAULT CURRENT_TIMESTAMP,
+            analysis_results TEXT
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS table_preferences (
+            db_path TEXT NOT NULL,
+            table_name TEXT NOT NULL,
+            preferences_json TEXT NOT NULL,
+            PRIMARY KEY (db_path, table_name)
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS scheduled_analyses (
+            db_path TEXT PRIMARY KEY,
+            interval_hours INTEGER NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            last_run_at DATETIME
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS connection_preferences (
+            db_path TEXT PRIMARY KEY,
+            cache_size_kb INTEGER,
+            mmap_size_mb INTEGER,
+            busy_timeout_ms INTEGER
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS wal_checkpoint_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            db_path TEXT NOT NULL,
+            timestamp DATETIME DEFAULT CURRENT_TIMESTAMP,
+            busy INTEGER NOT NULL,
+            log INTEGER NOT NULL,
+            checkpointed INTEGER NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS search_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            db_path TEXT NOT NULL,
+            table_name TEXT NOT NULL,
+            search_text TEXT NOT NULL,
+            searched_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS column_annotations (
+            db_path TEXT NOT NULL,
+            table_name TEXT NOT NULL,
+            column_name TEXT NOT NULL,
+            description TEXT NOT NULL,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (db_path, table_name, column_name)
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Row counts for get_tables, valid while the database file is unchanged and within the TTL
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS table_row_count_cache (
+            db_path TEXT NOT NULL,
+            table_name TEXT NOT NULL,
+            row_count INTEGER NOT NULL,
+            db_modified_ms INTEGER NOT NULL,
+            cached_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (db_path, table_name)
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // One row per app version that has been launched with this metadata.db
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS version_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            version TEXT NOT NULL UNIQUE,
+            first_seen_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Holds a single row with all app preferences as one JSON object
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS preferences (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            preferences_json TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Migration: Add analysis_results column if it doesn't exist
+    let columns = get_column_names(conn, "metadata")?;
+    if !columns.contains(&"analysis_results".to_string()) {
+        let _ = conn.execute("ALTER TABLE metadata ADD COLUMN analysis_results TEXT", []);
+    }
+
+    // Migration: Add is_encrypted column if it doesn't exist
+    if !columns.contains(&"is_encrypted".to_string()) {
+        let _ = conn.execute(
+            "ALTER TABLE metadata ADD COLUMN is_encrypted INTEGER NOT NULL DEFAULT 0",
+            [],
+        );
+    }
+
+    // Migration: Add file_hash column if it doesn't exist
+    if !columns.contains(&"file_hash".to_string()) {
+        let _ = conn.execute("ALTER TABLE metadata ADD COLUMN file_hash TEXT", []);
+    }
+
+    // Migration: Add deleted_at column if it doesn't exist
+    if !columns.contains(&"deleted_at".to_string()) {
+        let _ = conn.execute("ALTER TABLE metadata ADD COLUMN deleted_at DATETIME", []);
+    }
+
+    // Migration: Add icon column if it doesn't exist
+    if !columns.contains(&"icon".to_string()) {
+        let _ = conn.execute("ALTER TABLE metadata ADD COLUMN icon TEXT", []);
+    }
+
+    // Migration: Add notes column if it doesn't exist
+    if !columns.contains(&"notes".to_string()) {
+        let _ = conn.execute("ALTER TABLE metadata ADD COLUMN notes TEXT", []);
+    }
+
+    Ok(())
+}
+
+fn get_column_names(conn: &Connection, table: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({})", quote_identifier(table)))
+        .map_err(|e| e.to_string())?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(columns)
+}
+
+const METADATA_COLUMNS: &str =
+    "id, name, path, created_at, last_accessed, analysis_results, is_encrypted, icon, notes";
+
+fn metadata_from_row(row: &rusqlite::Row) -> rusqlite::Result<DatabaseMetadata> {
+    let path: String = row.get(2)?;
+    Ok(DatabaseMetadata {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        file_exists: Path::new(&path).exists(),
+        file_size_kb: std::fs::metadata(&path).ok().map(|m| m.len() / 1024),
+        path,
+        created_at: row.get(3)?,
+        last_accessed: row.get(4)?,
+        analysis_results: row.get(5)?,
+        is_encrypted: row.get(6)?,
+        icon: row.get(7)?,
+        notes: row.get(8)?,
+    })
+}
+
+fn get_metadata_by_path(conn: &Connection, path: &str) -> Result<DatabaseMetadata, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM metadata WHERE path = ?1", METADATA_COLUMNS),
+        params![path],
+        metadata_from_row,
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn insert_database_metadata(
+    conn: &Connection,
+    name: &str,
+    path: &str,
+    allow_duplicate: bool,
+    icon: Option<&str>,
+) -> Result<ImportResult, ImportError> {
+    // Relative paths and symlinks to the same file must map to one metadata row
+    let canonical = canonical_db_path(path).map_err(|e| ImportError::new("not_found", e))?;
+    let path = canonical.as_str();
+    adopt_canonical_path(conn, path).map_err(|e| ImportError::new("metadata", e))?;
+
+    // Check if it's a valid sqlite database
+    let test_conn = open_database_with_prefs(conn, path, OpenFlags::default())
+        .map_err(|e| ImportError::new("invalid", format!("Invalid SQLite database: {}", e)))?;
+
+    // Opening is lazy, encrypted (e.g. SQLCipher) files only fail on the first read
+    let probe = test_conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| {
+        row.get::<_, i64>(0)
+    });
+    match probe {
+        Ok(_) => {}
+        // A plain SQLite header means the file is corrupt rather than encrypted
+        Err(e)
+            if e.sqlite_error_code() == Some(rusqlite::ErrorCode::NotADatabase)
+                && !has_sqlite_header(path)
+                && looks_encrypted(path) =>
+        {
+            return Err(ImportError::new(
+                "encrypted",
+                "Database appears to be encrypted and can't be read without a key".to_string(),
+            ));
+        }
+        Err(e) => {
+            return Err(ImportError::new(
+                "invalid",
+                format!("Invalid SQLite database: {}", e),
+            ))
+        }
+    }
+
+    // A renamed or moved copy of an already imported file keeps its hash
+    let hash = file_hash(path).map_err(|e| ImportError::new("invalid", e))?;
+    if !allow_duplicate {
+        let existing = conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM metadata WHERE file_hash = ?1 AND path != ?2 AND deleted_at IS NULL",
+                    METADATA_COLUMNS
+                ),
+                params![hash, path],
+                metadata_from_row,
+            )
+            .optional()
+            .map_err(|e| ImportError::new("metadata", e.to_string()))?;
+        if let Some(metadata) = existing {
+            return Ok(ImportResult {
+                metadata,
+                is_duplicate: true,
+            });
+        }
+    }
+
+    // Re-importing keeps the row's id, notes, stored analysis and, unless a new
+    // one is given, its icon
+    conn.execute(
+        "INSERT INTO metadata (name, path, last_accessed, is_encrypted, file_hash, icon) VALUES (?1, ?2, CURRENT_TIMESTAMP, 0, ?3, ?4)
+         ON CONFLICT(path) DO UPDATE SET name = excluded.name, last_accessed = excluded.last_accessed, is_encrypted = excluded.is_encrypted, file_hash = excluded.file_hash, icon = COALESCE(excluded.icon, icon), deleted_at = NULL",
+        params![name, path, hash, icon],
+    )
+    .map_err(|e| ImportError::new("metadata", e.to_string()))?;
+
+    let metadata = get_metadata_by_path(conn, path).map_err(|e| ImportError::new("metadata", e))?;
+    Ok(ImportResult {
+        metadata,
+        is_duplicate: false,
+    })
+}
+
+// dunce keeps Windows paths in their usual C:\... form instead of the \\?\ form
+// std::fs::canonicalize returns, so stored paths stay readable and comparable
+fn canonical_db_path(path: &str) -> Result<String, String> {
+    let canonical = dunce::canonicalize(path)
+        .map_err(|e| format!("Could not resolve path '{}': {}", path, e))?;
+    canonical
+        .to_str()
+        .map(str::to_string)
+        .ok_or_else(|| format!("Path '{}' is not valid UTF-8", path))
+}
+
+const DB_PATH_TABLES: [&str; 7] = [
+    "table_preferences",
+    "scheduled_analyses",
+    "connection_preferences",
+    "wal_checkpoint_log",
+    "search_history",
+    "column_annotations",
+    "table_row_count_cache",
+];
+
+// Rows saved before paths were canonicalized may spell the same file differently,
+// the first such row is moved to the canonical path along with its per-database data
+fn adopt_canonical_path(conn: &Connection, canonical: &str) -> Result<(), String> {
+    let exists = conn
+        .query_row(
+            "SELECT 1 FROM metadata WHERE path = ?1",
+            params![canonical],
+            |_| Ok(()),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .is_some();
+    if exists {
+        return Ok(());
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT path FROM metadata")
+        .map_err(|e| e.to_string())?;
+    let paths = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let Some(legacy) = paths
+        .into_iter()
+        .find(|p| canonical_db_path(p).is_ok_and(|c| c == canonical))
+    else {
+        return Ok(());
+    };
+
+    conn.execute(
+        "UPDATE metadata SET path = ?1 WHERE path = ?2",
+        params![canonical, legacy],
+    )
+    .map_err(|e| e.to_string())?;
+    for table in DB_PATH_TABLES {
+        conn.execute(
+            &format!(
+                "UPDATE OR REPLACE {} SET db_path = ?1 WHERE db_path = ?2",
+                table
+            ),
+            params![canonical, legacy],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// SHA-256 of the first 64KB, which covers the header and schema pages
+fn file_hash(path: &str) -> Result<String, String> {
+    use std::io::Read;
+    let mut buffer = Vec::with_capacity(64 * 1024);
+    std::fs::File::open(path)
+        .and_then(|f| f.take(64 * 1024).read_to_end(&mut buffer))
+        .map_err(|e| e.to_string())?;
+    Ok(Sha256::digest(&buffer)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+fn has_sqlite_header(path: &str) -> bool {
+    use std::io::Read;
+    let mut header = [0u8; 16];
+    std::fs::File::open(path)
+        .and_then(|mut f| f.read_exact(&mut header))
+        .map(|_| &header == b"SQLite format 3\0")
+        .unwrap_or(false)
+}
+
+// Encrypted databases (e.g. SQLCipher) are whole pages of random-looking bytes,
+// which tells them apart from text files and other formats that fail to open
+fn looks_encrypted(path: &str) -> bool {
+    use std::io::Read;
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return false;
+    };
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    if len < 1024 || len % 512 != 0 {
+        return false;
+    }
+    let mut page = [0u8; 1024];
+    if file.read_exact(&mut page).is_err() {
+        return false;
+    }
+    let mut seen = [false; 256];
+    for byte in page {
+        seen[byte as usize] = true;
+    }
+    seen.iter().filter(|&&s| s).count() >= 200
+}
+
+#[tauri::command]
+async fn import_database(
+    state: State<'_, AppState>,
+    name: String,
+    path: String,
+    allow_duplicate: Option<bool>,
+    icon: Option<String>,
+) -> Result<ImportResult, ImportError> {
+    validate_icon(icon.as_deref()).map_err(|e| ImportError::new("invalid", e))?;
+    let conn = get_metadata_conn(&state).map_err(|e| ImportError::new("metadata", e))?;
+    insert_database_metadata(
+        &conn,
+        &name,
+        &path,
+        allow_duplicate.unwrap_or(false),
+        icon.as_deref(),
+    )
+}
+
+const MAX_ICON_LENGTH: usize = 64;
+
+fn validate_icon(icon: Option<&str>) -> Result<(), String> {
+    match icon {
+        Some(icon) if icon.chars().count() > MAX_ICON_LENGTH => Err(format!(
+            "Icon must be at most {} characters",
+            MAX_ICON_LENGTH
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[tauri::command]
+async fn import_database_batch(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    entries: Vec<ImportEntry>,
+) -> Result<Vec<Result<DatabaseMetadata, String>>, String> {
+    let mut conn = get_metadata_conn(&state)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let total = entries.len();
+    let mut done = 0;
+    let mut results = Vec::with_capacity(total);
+
+    // A failing entry is reported in its own slot; the rest of the batch still commits
+    for entry in &entries {
+        let result = match insert_database_metadata(&tx, &entry.name, &entry.path, false, None) {
+            Ok(imported) if imported.is_duplicate => {
+                Err(format!("Already imported as '{}'", imported.metadata.name))
+            }
+            Ok(imported) => Ok(imported.metadata),
+            Err(e) => Err(e.message),
+        };
+        if result.is_ok() {
+            done += 1;
+            let _ = app.emit("batch-import-progress", BatchImportProgress { done, total });
+        }
+        results.push(result);
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(results)
+}
+
+#[tauri::command]
+async fn list_databases(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    sort_by: Option<String>,
+    sort_dir: Option<String>,
+) -> Result<Vec<DatabaseMetadata>, String> {
+    let sort_by = sort_by.unwrap_or_else(|| "last_accessed".to_string());
+    let descending = match sort_dir.as_deref().unwrap_or("desc") {
+        "asc" => false,
+        "desc" => true,
+        other => {
+            return Err(format!(
+                "Unsupported sort direction '{}', expected asc or desc",
+                other
+            ))
+        }
+    };
+    // File sizes aren't stored, so size is sorted after reading the rows
+    let order_column = match sort_by.as_str() {
+        "last_accessed" | "size" => "last_accessed",
+        "name" => "name COLLATE NOCASE",
+        "created_at" => "created_at",
+        other => {
+            return Err(format!(
+                "Unsupported sort field '{}', expected last_accessed, name, created_at or size",
+                other
+            ))
+        }
+    };
+
+    let conn = get_metadata_conn(&state)?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM metadata WHERE deleted_at IS NULL ORDER BY {} {}",
+            METADATA_COLUMNS,
+            order_column,
+            if descending { "DESC" } else { "ASC" }
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let db_iter = stmt
+        .query_map([], metadata_from_row)
+        .map_err(|e| e.to_string())?;
+
+    let mut dbs = Vec::new();
+    for db in db_iter {
+        let db = db.map_err(|e| e.to_string())?;
+        // Files moved or deleted outside the app would otherwise only surface
+        // as IO errors from whichever command touches them next
+        if !db.file_exists {
+            let _ = app.emit("database-file-missing", &db.path);
+        }
+        dbs.push(db);
+    }
+
+    // Missing files have no size and go last in either direction
+    if sort_by == "size" {
+        dbs.sort_by(|a, b| match (a.file_size_kb, b.file_size_kb) {
+            (Some(a), Some(b)) if descending => b.cmp(&a),
+            (Some(a), Some(b)) => a.cmp(&b),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        });
+    }
+    Ok(dbs)
+}
+
+#[tauri::command]
+async fn list_databases_filtered(
+    state: State<'_, AppState>,
+    filter: DatabaseFilter,
+) -> Result<Vec<DatabaseMetadata>, String> {
+    // Databases can't be tagged yet, fail loudly rather than ignore the filter
+    if filter.tag.is_some() {
+        return Err("Filtering by tag is not supported yet".into());
+    }
+
+    let mut conditions = vec!["deleted_at IS NULL".to_string()];
+    let mut query_params: Vec<rusqlite::types::Value> = Vec::new();
+    if let Some(prefix) = filter.name_prefix.filter(|p| !p.is_empty()) {
+        conditions.push("lower(substr(name, 1, length(?))) = lower(?)".to_string());
+        query_params.push(rusqlite::types::Value::Text(prefix.clone()));
+        query_params.push(rusqlite::types::Value::Text(prefix));
+    }
+    for (bound, op) in [
+        (filter.accessed_after, ">="),
+        (filter.accessed_before, "<="),
+    ] {
+        if let Some(bound) = bound {
+            if !is_iso8601(&bound) {
+                return Err(format!("'{}' is not a valid ISO 8601 date", bound));
+            }
+            // datetime() normalizes T separators and offsets to the stored format
+            conditions.push(format!("last_accessed {} datetime(?)", op));
+            query_params.push(rusqlite::types::Value::Text(bound));
+        }
+    }
+    match filter.has_analysis {
+        Some(true) => conditions.push("analysis_results IS NOT NULL".to_string()),
+        Some(false) => conditions.push("analysis_results IS NULL".to_string()),
+        None => {}
+    }
+    query_params.push(rusqlite::types::Value::Integer(filter.limit.unwrap_or(-1)));
+
+    let conn = get_metadata_conn(&state)?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM metadata WHERE {} ORDER BY last_accessed DESC LIMIT ?",
+            METADATA_COLUMNS,
+            conditions.join(" AND ")
+        ))
+        .map_err(|e| e.to_string())?;
+    let dbs = stmt
+        .query_map(params_from_iter(&query_params), metadata_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(dbs)
+}
+
+#[tauri::command]
+async fn check_database_files(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let conn = get_metadata_conn(&state)?;
+    let mut stmt = conn
+        .prepare("SELECT path FROM metadata WHERE deleted_at IS NULL ORDER BY last_accessed DESC")
+        .map_err(|e| e.to_string())?;
+    let paths: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(paths
+        .into_iter()
+        .filter(|path| !Path::new(path).exists())
+        .collect())
+}
+
+#[tauri::command]
+async fn stop_db_analysis(state: State<'_, AppState>, path: String) -> Result<(), String> {
+    let mut tasks = state
+        .analysis_tasks
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if let Some(token) = tasks.remove(&path) {
+        token.store(true, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+// Lock calls recover from poisoning on their own, this also stops every running
+// analysis and reloads the cached preferences in case a panic left them half-written
+#[tauri::command]
+async fn recover_state(state: State<'_, AppState>) -> Result<(), String> {
+    let mut tasks = state
+        .analysis_tasks
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    for token in tasks.values() {
+        token.store(true, Ordering::SeqCst);
+    }
+    tasks.clear();
+    drop(tasks);
+
+    let conn = get_metadata_conn(&state)?;
+    *state.preferences.lock().unwrap_or_else(|e| e.into_inner()) = load_app_preferences(&conn)?;
+
+    state.analysis_tasks.clear_poison();
+    state.wal_checkpoints.clear_poison();
+    state.scheduled_analyses.clear_poison();
+    state.preferences.clear_poison();
+    state.row_streams.clear_poison();
+    state.active_queries.clear_poison();
+    Ok(())
+}
+
+#[tauri::command]
+async fn start_db_analysis(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<(), String> {
+    let cancellation_token = register_analysis_task(&state, &path);
+    let metadata_db_path = state.metadata_db_path.clone();
+    let chunk_size = analysis_chunk_size(&metadata_db_path);
+
+    tauri::async_runtime::spawn(async move {
+        analyze_and_store(
+            &app,
+            &path,
+            cancellation_token,
+            chunk_size,
+            &metadata_db_path,
+        )
+        .await;
+    });
+
+    Ok(())
+}
+
+fn register_analysis_task(state: &AppState, path: &str) -> Arc<AtomicBool> {
+    let cancellation_token = Arc::new(AtomicBool::new(false));
+    let mut tasks = state
+        .analysis_tasks
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    // If a task is already running for this path, stop it first
+    if let Some(old_token) = tasks.get(path) {
+        old_token.store(true, Ordering::SeqCst);
+    }
+    tasks.insert(path.to_string(), cancellation_token.clone());
+    cancellation_token
+}
+
+fn analysis_chunk_size(metadata_db_path: &Path) -> u64 {
+    Connection::open(metadata_db_path)
+        .map_err(|e| e.to_string())
+        .and_then(|conn| load_app_preferences(&conn))
+        .map(|prefs| prefs.analysis_chunk_size)
+        .unwrap_or(DEFAULT_ANALYSIS_CHUNK_SIZE)
+}
+
+async fn analyze_and_store(
+    app: &tauri::AppHandle,
+    path: &str,
+    cancellation_token: Arc<AtomicBool>,
+    chunk_size: u64,
+    metadata_db_path: &Path,
+) {
+    log_debug(
+        "Starting background analysis",
+        serde_json::json!({"path": path}),
+        "B",
+    );
+    let result = run_analysis(app, path, cancellation_token, chunk_size, metadata_db_path).await;
+
+    // Remove task from active tasks
+    if let Some(state) = app.try_state::<AppState>() {
+        let mut tasks = state
+            .analysis_tasks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        tasks.remove(path);
+    }
+
+    match result {
+        Ok(analysis) => {
+            log_debug(
+                if analysis.partial_analysis.is_some() {
+                    "Analysis cancelled, partial results kept for resuming"
+                } else {
+                    "Analysis finished successfully"
+                },
+                serde_json::json!({"path": path}),
+                "B",
+            );
+            // Save results to metadata DB. Partial results from a cancelled run
+            // never replace complete ones
+            if let Ok(conn) = Connection::open(metadata_db_path) {
+                let json_results = serde_json::to_string(&analysis).unwrap_or_default();
+                let _ = conn.execute(
+                    "UPDATE metadata SET analysis_results = ?1 WHERE path = ?2 AND (?3 = 0 OR analysis_results IS NULL OR json_extract(analysis_results, '$.partial_analysis') IS NOT NULL)",
+                    params![json_results, path, analysis.partial_analysis.is_some()],
+                );
+            }
+        }
+        Err(e) => {
+            log_debug(
+                "Analysis failed or cancelled",
+                serde_json::json!({"path": path, "error": e}),
+                "B",
+            );
+        }
+    }
+}
+
+enum AnalysisMessage {
+    Progress(AnalysisProgress),
+    Finished(Box<Result<AnalysisResults, String>>),
+}
+
+// Runs the analysis on a dedicated OS thread so the blocking SQLite reads don't
+// tie up an async executor thread, relaying its progress as events.
+async fn run_analysis(
+    app: &tauri::AppHandle,
+    db_path: &str,
+    cancel: Arc<AtomicBool>,
+    chunk_size: u64,
+    metadata_db_path: &Path,
+) -> Result<AnalysisResults, String> {
+    let (tx, mut rx) = mpsc::channel(64);
+    let worker_path = db_path.to_string();
+    let worker_metadata_path = metadata_db_path.to_path_buf();
+
+    std::thread::spawn(move || {
+        let result =
+            analyze_database_internal(&worker_path, cancel, chunk_size, &worker_metadata_path, &tx);
+        let _ = tx.blocking_send(AnalysisMessage::Finished(Box::new(result)));
+    });
+
+    while let Some(message) = rx.recv().await {
+        match message {
+            AnalysisMessage::Progress(progress) => {
+                let _ = app.emit("analysis-progress", progress);
+            }
+            AnalysisMessage::Finished(result) => return *result,
+        }
+    }
+
+    Err("Analysis worker stopped unexpectedly".into())
+}
+
+fn analyze_database_internal(
+    db_path: &str,
+    cancel: Arc<AtomicBool>,
+    chunk_size: u64,
+    metadata_db_path: &Path,
+    progress: &mpsc::Sender<AnalysisMessage>,
+) -> Result<AnalysisResults, String> {
+    let chunk_size = chunk_size.max(1);
+    let prefs_conn = Connection::open(metadata_db_path).map_err(|e| e.to_string())?;
+    let conn = open_database_with_prefs(&prefs_conn, db_path, OpenFlags::default())?;
+
+    // A previously cancelled run left results for the tables it finished
+    let mut results = prefs_conn
+        .query_row(
+            "SELECT analysis_results FROM metadata WHERE path = ?1",
+            params![db_path],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .and_then(|json| serde_json::from_str::<AnalysisResults>(&json).ok())
+        .filter(|previous| previous.partial_analysis.is_some())
+        .unwrap_or_default();
+    let resuming = results.partial_analysis.is_some();
+    let completed_tables = results
+        .partial_analysis
+        .as_ref()
+        .map(|partial| partial.completed_tables.clone())
+        .unwrap_or_default();
+
+    // Get all tables and their row counts
+    let mut stmt = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%'")
+        .map_err(|e| e.to_string())?;
+    let tables: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|table: &String| !completed_tables.contains(table))
+        .collect();
+
+    let mut total_records = 0;
+    for table in &tables {
+        let count: u64 = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM {}", quote_identifier(table)),
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(0);
+        total_records += count;
+    }
+
+    // Text is read back through rusqlite as UTF-8, so for UTF-16 databases the
+    // char counts describe the decoded text rather than what's stored on disk.
+    let encoding = get_db_encoding(&conn)?;
+    if encoding != "UTF-8" && !resuming {
+        results.warnings.push(format!(
+            "Database uses {} encoding; character counts are based on UTF-8 decoded text and may not match the stored data",
+            encoding
+        ));
+    }
+
+    let max_parallelism = load_app_preferences(&prefs_conn)
+        .map(|prefs| prefs.analysis_max_parallelism)
+        .unwrap_or(DEFAULT_ANALYSIS_MAX_PARALLELISM)
+        .clamp(1, tables.len().max(1));
+
+    let scan = AnalysisScan {
+        db_path,
+        metadata_db_path,
+        tables: &tables,
+        next_table: AtomicUsize::new(0),
+        failed: AtomicBool::new(false),
+        cancel: &cancel,
+        records_processed: AtomicU64::new(0),
+        total_records,
+        chunk_size,
+        start_time: Instant::now(),
+        progress,
+    };
+
+    // Up to max_parallelism workers, each with its own connection, take tables
+    // one at a time until none are left
+    let partials = std::thread::scope(|s| {
+        let workers: Vec<_> = (0..max_parallelism)
+            .map(|_| {
+                s.spawn(|| {
+                    let result = analyze_tables_worker(&scan);
+                    if result.is_err() {
+                        scan.failed.store(true, Ordering::SeqCst);
+                    }
+                    result
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|_| Err("Analysis worker panicked".into()))
+            })
+            .collect::<Result<Vec<_>, String>>()
+    })?;
+
+    let mut results = partials
+        .into_iter()
+        .fold(results, |acc, partial| acc + partial);
+
+    // Cancelled runs keep the list of finished tables so the next run can resume
+    if cancel.load(Ordering::SeqCst) {
+        results
+            .partial_analysis
+            .get_or_insert_with(PartialAnalysis::default);
+    } else {
+        results.partial_analysis = None;
+    }
+    Ok(results)
+}
+
+struct AnalysisScan<'a> {
+    db_path: &'a str,
+    metadata_db_path: &'a Path,
+    tables: &'a [String],
+    next_table: AtomicUsize,
+    failed: AtomicBool, // set when a worker errors so the others stop taking tables
+    cancel: &'a AtomicBool,
+    records_processed: AtomicU64,
+    total_records: u64,
+    chunk_size: u64,
+    start_time: Instant,
+    progress: &'a mpsc::Sender<AnalysisMessage>,
+}
+
+fn analyze_tables_worker(scan: &AnalysisScan) -> Result<AnalysisResults, String> {
+    let prefs_conn = Connection::open(scan.metadata_db_path).map_err(|e| e.to_string())?;
+    let conn = open_database_with_prefs(&prefs_conn, scan.db_path, OpenFlags::default())?;
+
+    // Only fully scanned tables are kept, a table cut short by cancellation is dropped
+    let mut results = AnalysisResults::default();
+    loop {
+        if scan.cancel.load(Ordering::SeqCst) || scan.failed.load(Ordering::SeqCst) {
+            break;
+        }
+        let Some(table) = scan
+            .tables
+            .get(scan.next_table.fetch_add(1, Ordering::SeqCst))
+        else {
+            break;
+        };
+
+        let mut table_results = AnalysisResults::default();
+        match analyze_table_rows(&conn, table, scan, &mut table_results) {
+            Ok(()) => {
+                table_results.partial_analysis = Some(PartialAnalysis {
+                    completed_tables: vec![table.clone()],
+                });
+                results.merge(table_results);
+            }
+            Err(_) if scan.cancel.load(Ordering::SeqCst) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(results)
+}
+
+fn analyze_table_rows(
+    conn: &Connection,
+    table: &str,
+    scan: &AnalysisScan,
+    results: &mut AnalysisResults,
+) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT * FROM {}", quote_identifier(table)))
+        .map_err(|e| e.to_string())?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
+    let declared_types: HashMap<String, String> = get_table_columns(conn, table)?
+        .into_iter()
+        .map(|c| (c.name, c.declared_type))
+        .collect();
+    let integer_columns: Vec<bool> = columns
+        .iter()
+        .map(|c| {
+            declared_types
+                .get(c)
+                .is_some_and(|t| column_affinity(t) == "INTEGER")
+        })
+        .collect();
+    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        if scan.cancel.load(Ordering::SeqCst) {
+            return Err("Analysis cancelled".into());
+        }
+
+        for (i, col_name) in columns.iter().enumerate() {
+            let value: rusqlite::types::Value = row.get(i).unwrap_or(rusqlite::types::Value::Null);
+
+            // First distinct non-null values in scan order, so reruns give the same preview
+            let samples = results
+                .column_sample_values
+                .entry(format!("{}.{}", table, col_name))
+                .or_default();
+            if samples.len() < COLUMN_SAMPLE_LIMIT {
+                let sample = match &value {
+                    rusqlite::types::Value::Null => None,
+                    rusqlite::types::Value::Integer(i) => Some(serde_json::json!(i)),
+                    rusqlite::types::Value::Real(f) => Some(serde_json::json!(f)),
+                    rusqlite::types::Value::Text(s) => {
+                        Some(serde_json::Value::String(s.chars().take(100).collect()))
+                    }
+                    rusqlite::types::Value::Blob(b) => Some(serde_json::json!(b.len())),
+                };
+                if let Some(sample) = sample {
+                    if !samples.contains(&sample) {
+                        samples.push(sample);
+                    }
+                }
+            }
+
+            match value {
+                rusqlite::types::Value::Text(s) => {
+                    // INTEGER affinity only keeps text that couldn't be converted
+                    if integer_columns[i] && s.trim().parse::<i64>().is_err() {
+                        *results
+                            .column_type_mismatches
+                            .entry(format!("{}.{}", table, col_name))
+                            .or_insert(0) += 1;
+                    }
+
+                    let char_count = s.chars().count();
+                    let max_length = results
+                        .column_max_lengths
+                        .entry(format!("{}.{}", table, col_name))
+                        .or_insert(0);
+                    *max_length = (*max_length).max(char_count);
+
+                    results.total_chars += char_count as u64;
+                    for c in s.chars() {
+                        *results.char_frequency.entry(c as u32).or_insert(0) += 1;
+                        if c.is_numeric() {
+                            results.type_distribution.numeric += 1;
+                        } else if c.is_alphabetic() {
+                            results.type_distribution.alphabets += 1;
+                        } else {
+                            results.type_distribution.special += 1;
+                        }
+                    }
+
+                    // Simple format detection
+                    let format_key = format!("{}.{}", table, col_name);
+                    let formats = results.column_formats.entry(format_key).or_default();
+                    if s.contains('@') && s.contains('.') && !formats.contains(&"Email".to_string())
+                    {
+                        formats.push("Email".into());
+                    }
+                    if (s.starts_with("http") || s.starts_with("www"))
+                        && !formats.contains(&"URL".to_string())
+                    {
+                        formats.push("URL".into());
+                    }
+                }
+                rusqlite::types::Value::Integer(_) | rusqlite::types::Value::Real(_) => {
+                    results.type_distribution.numeric += 1;
+                }
+                rusqlite::types::Value::Blob(b) => {
+                    let max_length = results
+                        .column_max_lengths
+                        .entry(format!("{}.{}", table, col_name))
+                        .or_insert(0);
+                    *max_length = (*max_length).max(b.len());
+
+                    results.total_chars += b.len() as u64;
+                    results.type_distribution.unknown += 1;
+                }
+                rusqlite::types::Value::Null => {}
+            }
+        }
+
+        // Emit progress every chunk_size records to not flood the frontend
+        let records_processed = scan.records_processed.fetch_add(1, Ordering::SeqCst) + 1;
+        if records_processed.is_multiple_of(scan.chunk_size)
+            || records_processed == scan.total_records
+        {
+            scan.report_progress(records_processed);
+        }
+    }
+
+    Ok(())
+}
+
+impl AnalysisScan<'_> {
+    fn report_progress(&self, records_processed: u64) {
+        let db_path = self.db_path;
+        let total_records = self.total_records;
+        let elapsed = self.start_time.elapsed().as_secs_f64();
+        let speed = if elapsed > 0.0 {
+            records_processed as f64 / elapsed
+        } else {
+            0.0
+        };
+        let remaining = if speed > 0.0 {
+            (total_records - records_processed) as f64 / speed
+        } else {
+            0.0
+        };
+
+        let _ = self
+            .progress
+            .blocking_send(AnalysisMessage::Progress(AnalysisProgress {
+                db_path: db_path.to_string(),
+                progress: (records_processed as f64 / total_records as f64) * 100.0,
+                records_processed,
+                total_records,
+                time_remaining_secs: remaining as u64,
+                speed_records_per_sec: speed,
+                is_finished: records_processed == total_records,
+            }));
+
+        if records_processed.is_multiple_of(1000) || records_processed == total_records {
+            log_debug(
+                "Analysis progress update",
+                serde_json::json!({
+                    "db_path": db_path,
+                    "progress": (records_processed as f64 / total_records as f64) * 100.0,
+                    "records": records_processed,
+                    "total": total_records
+                }),
+                "C",
+            );
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_tables(
+    state: State<'_, AppState>,
+    path: String,
+    hide_fts_shadow: Option<bool>,
+) -> Result<Vec<TableInfo>, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let cache_conn = get_metadata_conn(&state)?;
+    let cache_ttl = format!(
+        "-{} seconds",
+        state
+            .preferences
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .row_count_cache_ttl_secs
+    );
+    let modified_ms = db_modified_ms(&path);
+    let shadow_tables = if hide_fts_shadow.unwrap_or(false) {
+        get_fts_shadow_tables(&conn)?
+    } else {
+        Vec::new()
+    };
+
+    let mut stmt = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%'")
+        .map_err(|e| e.to_string())?;
+
+    let table_names = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?;
+
+    let mut tables = Vec::new();
+    for name_result in table_names {
+        let name = name_result.map_err(|e| e.to_string())?;
+        if shadow_tables.contains(&name) {
+            continue;
+        }
+
+        // The cache only saves work, a failing lookup or write falls back to counting
+        let cached: Option<i64> = cache_conn
+            .query_row(
+                "SELECT row_count FROM table_row_count_cache WHERE db_path = ?1 AND table_name = ?2 AND db_modified_ms = ?3 AND cached_at >= datetime('now', ?4)",
+                params![path, name, modified_ms, cache_ttl],
+                |row| row.get(0),
+            )
+            .ok();
+        let row_count = match cached {
+            Some(row_count) => row_count,
+            None => match conn.query_row(
+                &format!("SELECT COUNT(*) FROM {}", quote_identifier(&name)),
+                [],
+                |row| row.get::<_, i64>(0),
+            ) {
+                Ok(row_count) => {
+                    let _ = cache_conn.execute(
+                        "INSERT OR REPLACE INTO table_row_count_cache (db_path, table_name, row_count, db_modified_ms, cached_at) VALUES (?1, ?2, ?3, ?4, CURRENT_TIMESTAMP)",
+                        params![path, name, row_count, modified_ms],
+                    );
+                    row_count
+                }
+                Err(_) => 0,
+            },
+        };
+
+        tables.push(TableInfo { name, row_count });
+    }
+    Ok(tables)
+}
+
+// Latest modification of the database or its WAL, in WAL mode writes only touch
+// the -wal file until the next checkpoint
+fn db_modified_ms(path: &str) -> i64 {
+    [path.to_string(), format!("{}-wal", path)]
+        .iter()
+        .filter_map(|p| std::fs::metadata(p).and_then(|m| m.modified()).ok())
+        .filter_map(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as i64)
+        .max()
+        .unwrap_or(0)
+}
+
+#[tauri::command]
+async fn invalidate_row_count_cache(
+    state: State<'_, AppState>,
+    db_path: String,
+) -> Result<(), String> {
+    let conn = get_metadata_conn(&state)?;
+    conn.execute(
+        "DELETE FROM table_row_count_cache WHERE db_path = ?1",
+        params![db_path],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+const DEFAULT_STREAM_BATCH_SIZE: u32 = 200;
+
+// Rows arrive as table-row-batch events. Full batches are sent as they fill up and
+// the final batch, which may be empty, has is_last set
+#[tauri::command]
+async fn stream_table_rows(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+    batch_size: Option<u32>,
+    stream_id: String,
+) -> Result<(), String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    if !table_exists(&conn, &table)? {
+        return Err(format!("Table '{}' does not exist", table));
+    }
+    let batch_size = batch_size.unwrap_or(DEFAULT_STREAM_BATCH_SIZE).max(1) as usize;
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut streams = state.row_streams.lock().unwrap_or_else(|e| e.into_inner());
+        // Reusing an id replaces the stream that had it
+        if let Some(old_token) = streams.insert(stream_id.clone(), cancel.clone()) {
+            old_token.store(true, Ordering::SeqCst);
+        }
+    }
+
+    std::thread::spawn(move || {
+        if let Err(e) = stream_rows(&app, &conn, &table, batch_size, &stream_id, &cancel) {
+            let _ = app.emit(
+                "table-row-stream-error",
+                serde_json::json!({"stream_id": stream_id, "error": e}),
+            );
+        }
+
+        if let Some(state) = app.try_state::<AppState>() {
+            let mut streams = state.row_streams.lock().unwrap_or_else(|e| e.into_inner());
+            if streams
+                .get(&stream_id)
+                .is_some_and(|token| Arc::ptr_eq(token, &cancel))
+            {
+                streams.remove(&stream_id);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn stream_rows(
+    app: &tauri::AppHandle,
+    conn: &Connection,
+    table: &str,
+    batch_size: usize,
+    stream_id: &str,
+    cancel: &AtomicBool,
+) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT * FROM {}", quote_identifier(table)))
+        .map_err(|e| e.to_string())?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
+    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+
+    let mut batch_index = 0;
+    let mut batch = Vec::with_capacity(batch_size);
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        if cancel.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        batch.push(row_to_json_values(row, columns.len()).map_err(|e| e.to_string())?);
+        if batch.len() == batch_size {
+            let _ = app.emit(
+                "table-row-batch",
+                TableRowBatch {
+                    stream_id: stream_id.to_string(),
+                    batch_index,
+                    columns: columns.clone(),
+                    rows: std::mem::replace(&mut batch, Vec::with_capacity(batch_size)),
+                    is_last: false,
+                },
+            );
+            batch_index += 1;
+        }
+    }
+
+    let _ = app.emit(
+        "table-row-batch",
+        TableRowBatch {
+            stream_id: stream_id.to_string(),
+            batch_index,
+            columns,
+            rows: batch,
+            is_last: true,
+        },
+    );
+    Ok(())
+}
+
+#[tauri::command]
+async fn cancel_stream(state: State<'_, AppState>, stream_id: String) -> Result<(), String> {
+    if let Some(token) = state
+        .row_streams
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&stream_id)
+    {
+        token.store(true, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+// FTS3/4/5 keep their index in ordinary tables named after the virtual table
+const FTS_SHADOW_SUFFIXES: [&str; 8] = [
+    "_content",
+    "_data",
+    "_idx",
+    "_docsize",
+    "_config",
+    "_segments",
+    "_segdir",
+    "_stat",
+];
+
+fn get_fts_shadow_tables(conn: &Connection) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT name, sql FROM sqlite_master WHERE type='table' AND sql LIKE 'CREATE VIRTUAL TABLE%'")
+        .map_err(|e| e.to_string())?;
+    let rows: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut shadow_tables = Vec::new();
+    for (name, sql) in rows {
+        let (module, _) = parse_virtual_table_sql(&sql);
+        if module.to_ascii_lowercase().starts_with("fts") {
+            for suffix in FTS_SHADOW_SUFFIXES {
+                shadow_tables.push(format!("{}{}", name, suffix));
+            }
+        }
+    }
+    Ok(shadow_tables)
+}
+
+#[tauri::command]
+async fn get_table_data(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+    query: TableQuery,
+) -> Result<TableData, String> {
+    let TableQuery {
+        page,
+        page_size,
+        search,
+        datetime_filter,
+        filter_in,
+        reverse,
+        hide_rowid,
+        distinct,
+        keyset_pagination,
+        cursor,
+        include_type_info,
+        change_detection_token,
+        timeout_ms,
+        query_id,
+    } = query;
+    let page = page.unwrap_or(1);
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let timeout_ms = set_query_timeout(&conn, timeout_ms)?;
+    let active_query = register_active_query(&state, query_id, &conn);
+
+    // Keep a single page from pulling an entire huge table into memory
+    let max_page_size = state
+        .preferences
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .max_page_size
+        .max(1);
+    if page_size < 1 {
+        return Err(format!("Page size must be at least 1, got {}", page_size));
+    }
+    if page < 1 {
+        return Err(format!("Page must be at least 1, got {}", page));
+    }
+    let mut warnings = Vec::new();
+    let clamped = page_size > max_page_size;
+    let page_size = if clamped {
+        warnings.push(format!(
+            "Page size {} exceeds the maximum of {}, only {} rows were returned",
+            page_size, max_page_size, max_page_size
+        ));
+        max_page_size
+    } else {
+        page_size
+    };
+
+    // Get columns
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({})", quote_identifier(&table)))
+        .map_err(|e| e.to_string())?;
+    let columns: Vec<String> = stmt
+        .query_map([], |row| row.get::<_, String>(1))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut conditions: Vec<String> = Vec::new();
+    let mut query_params: Vec<rusqlite::types::Value> = Vec::new();
+
+    // FTS5 tables are searched with MATCH, LIKE would scan and ignore the index
+    let is_fts = is_fts5_table(&conn, &table)?;
+    let fts_search = is_fts && search.as_deref().is_some_and(|s| !s.is_empty());
+
+    // Prepare search condition
+    if let Some(ref s) = search {
+        if fts_search {
+            conditions.push(format!("{} MATCH ?", quote_identifier(&table)));
+            query_params.push(rusqlite::types::Value::Text(s.clone()));
+        } else if !s.is_empty() {
+            let (condition, search_params) = table_search_condition(&columns, s);
+            conditions.push(condition);
+            query_params.extend(search_params);
+        }
+    }
+
+    // Prepare date range condition
+    if let Some(filter) = datetime_filter {
+        if !columns.contains(&filter.column) {
+            return Err(format!(
+                "Column '{}' does not exist in '{}'",
+                filter.column, table
+            ));
+        }
+        if let Some(after) = filter.after {
+            if !is_iso8601(&after) {
+                return Err(format!("'{}' is not a valid ISO 8601 date", after));
+            }
+            conditions.push(format!("{} >= ?", quote_identifier(&filter.column)));
+            query_params.push(rusqlite::types::Value::Text(after));
+        }
+        if let Some(before) = filter.before {
+            if !is_iso8601(&before) {
+                return Err(format!("'{}' is not a valid ISO 8601 date", before));
+            }
+            conditions.push(format!("{} <= ?", quote_identifier(&filter.column)));
+            query_params.push(rusqlite::types::Value::Text(before));
+        }
+    }
+
+    // Fetch specific rows, e.g. by a list of primary keys
+    if let Some(filter) = filter_in {
+        if !columns.contains(&filter.column) {
+            return Err(format!(
+                "Column '{}' does not exist in '{}'",
+                filter.column, table
+            ));
+        }
+        if filter.values.len() > 1000 {
+            return Err(format!(
+                "Too many values for IN filter ({}), the maximum is 1000",
+                filter.values.len()
+            ));
+        }
+        // IN () is valid SQLite and matches nothing
+        conditions.push(format!(
+            "{} IN ({})",
+            quote_identifier(&filter.column),
+            vec!["?"; filter.values.len()].join(", ")
+        ));
+        query_params.extend(filter.values.iter().map(json_to_sqlite_value));
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", conditions.join(" AND "))
+    };
+
+    // DISTINCT over every column collapses exact duplicate rows, so comparing
+    // its total with the plain one tells how many duplicates the table holds
+    let select_keyword = if distinct.unwrap_or(false) {
+        "SELECT DISTINCT"
+    } else {
+        "SELECT"
+    };
+
+    // Get total count for pagination
+    let total_records: i64 = conn
+        .query_row(
+            &format!(
+                "SELECT COUNT(*) FROM ({} * FROM {} {})",
+                select_keyword,
+                quote_identifier(&table),
+                where_clause
+            ),
+            params_from_iter(&query_params),
+            |row| row.get(0),
+        )
+        .map_err(|e| active_query.map_error(e, timeout_ms))?;
+
+    let total_pages = if page_size > 0 {
+        (total_records + page_size - 1) / page_size
+    } else {
+        0
+    };
+    let offset = (page - 1) * page_size;
+
+    // Newest-first browsing. rowid order only means insertion order for regular
+    // rowid tables; WITHOUT ROWID tables have no rowid and the query will fail.
+    let mut order_clause = if reverse.unwrap_or(false) {
+        " ORDER BY rowid DESC".to_string()
+    } else {
+        String::new()
+    };
+    let mut limit_clause = format!(" LIMIT {} OFFSET {}", page_size, offset);
+
+    // Keyset pagination seeks past the last seen value instead of skipping rows
+    let mut row_conditions = conditions;
+    let mut row_params = query_params;
+    let mut backward = false;
+    if let Some(keyset) = &keyset_pagination {
+        if !columns.contains(&keyset.column) {
+            return Err(format!(
+                "Column '{}' does not exist in '{}'",
+                keyset.column, table
+            ));
+        }
+        backward = match keyset.direction.as_str() {
+            "forward" => false,
+            "backward" => true,
+            other => {
+                return Err(format!(
+                    "Unsupported direction '{}', expected forward or backward",
+                    other
+                ))
+            }
+        };
+        if !is_indexed_column(&conn, &table, &keyset.column)? {
+            warnings.push(format!(
+                "Column '{}' is not indexed, keyset pagination will scan the table",
+                keyset.column
+            ));
+        }
+
+        let (op, direction) = if backward {
+            ("<", "DESC")
+        } else {
+            (">", "ASC")
+        };
+        // A null last_value means the first page in that direction
+        if !keyset.last_value.is_null() {
+            row_conditions.push(format!("{} {} ?", quote_identifier(&keyset.column), op));
+            row_params.push(json_to_sqlite_value(&keyset.last_value));
+        }
+        order_clause = format!(
+            " ORDER BY {} {}",
+            quote_identifier(&keyset.column),
+            direction
+        );
+        // One extra row tells whether another page follows
+        limit_clause = format!(" LIMIT {}", page_size + 1);
+    }
+
+    // Cursor pagination seeks by rowid and keeps no state on this side. An empty
+    // cursor starts at the first row; page is then only used for total_pages
+    let row_cursor = match cursor.as_deref() {
+        None => None,
+        Some(_) if keyset_pagination.is_some() => {
+            return Err("cursor can't be combined with keyset pagination".into())
+        }
+        Some(_) if reverse.unwrap_or(false) || distinct.unwrap_or(false) => {
+            return Err("cursor can't be combined with reverse or distinct".into())
+        }
+        Some("") => Some(None),
+        Some(token) => Some(Some(RowCursor::decode(token)?)),
+    };
+    if let Some(row_cursor) = &row_cursor {
+        backward = row_cursor.as_ref().is_some_and(|c| c.backward);
+        if let Some(row_cursor) = row_cursor {
+            row_conditions.push(format!("rowid {} ?", if backward { "<" } else { ">" }));
+            row_params.push(rusqlite::types::Value::Integer(row_cursor.rowid));
+        }
+        order_clause = format!(" ORDER BY rowid {}", if backward { "DESC" } else { "ASC" });
+        limit_clause = format!(" LIMIT {}", page_size + 1);
+    }
+
+    let row_where_clause = if row_conditions.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", row_conditions.join(" AND "))
+    };
+
+    // Fetch rows, FTS5 searches also return a snippet as an extra trailing column
+    let snippet_column = if fts_search {
+        format!(
+            ", snippet({}, -1, '<mark>', '</mark>', '...', 16)",
+            quote_identifier(&table)
+        )
+    } else {
+        String::new()
+    };
+    // Cursor pages also select the rowid to build the next and previous cursors from
+    let rowid_column = if row_cursor.is_some() { ", rowid" } else { "" };
+    let query = format!(
+        "{} *{}{} FROM {} {}{}{}",
+        select_keyword,
+        snippet_column,
+        rowid_column,
+        quote_identifier(&table),
+        row_where_clause,
+        order_clause,
+        limit_clause
+    );
+    let mut stmt = conn
+        .prepare(&query)
+        .map_err(|e| map_rowid_error(e, &table))?;
+    let col_count =
+        stmt.column_count() - usize::from(fts_search) - usize::from(row_cursor.is_some());
+
+    // JSON alone can't tell the integer 1 from the text "1"
+    let include_type_info = include_type_info.unwrap_or(false);
+    let rows_iter = stmt
+        .query_map(params_from_iter(&row_params), |row| {
+            let types = if include_type_info {
+                row_to_type_names(row, col_count)?
+            } else {
+                Vec::new()
+            };
+            let snippet = if fts_search {
+                row.get::<_, Option<String>>(col_count)?.unwrap_or_default()
+            } else {
+                String::new()
+            };
+            let rowid = if row_cursor.is_some() {
+                row.get::<_, i64>(col_count + usize::from(fts_search))?
+            } else {
+                0
+            };
+            Ok((row_to_json_values(row, col_count)?, types, snippet, rowid))
+        })
+        .map_err(|e| active_query.map_error(e, timeout_ms))?;
+
+    let mut rows = Vec::new();
+    let mut row_types = Vec::new();
+    let mut snippets = Vec::new();
+    let mut rowids = Vec::new();
+    for row in rows_iter {
+        let (values, types, snippet, rowid) =
+            row.map_err(|e| active_query.map_error(e, timeout_ms))?;
+        rows.push(values);
+        row_types.push(types);
+        snippets.push(snippet);
+        rowids.push(rowid);
+    }
+
+    // The pagination count plus the highest rowid catches inserts and deletes by
+    // other processes. MAX(rowid) is a single b-tree seek; WITHOUT ROWID tables
+    // only get the count. The token follows the current search and filters, so
+    // it only compares equal across calls with the same query.
+    let max_rowid: Option<i64> = conn
+        .query_row(
+            &format!("SELECT MAX(rowid) FROM {}", quote_identifier(&table)),
+            [],
+            |row| row.get(0),
+        )
+        .unwrap_or(None);
+    let change_token = format!("{}:{}", total_records, max_rowid.unwrap_or_default());
+    let data_changed = change_detection_token.is_some_and(|token| token != change_token);
+
+    // History is best effort, a locked metadata DB shouldn't fail the query
+    if let Some(s) = search.as_deref().filter(|s| !s.is_empty()) {
+        if let Ok(metadata_conn) = get_metadata_conn(&state) {
+            let _ = metadata_conn.execute(
+                "INSERT INTO search_history (db_path, table_name, search_text) VALUES (?1, ?2, ?3)",
+                params![path, table, s],
+            );
+        }
+    }
+
+    let has_more = if keyset_pagination.is_some() || row_cursor.is_some() {
+        let has_more = rows.len() as i64 > page_size;
+        rows.truncate(page_size.max(0) as usize);
+        row_types.truncate(page_size.max(0) as usize);
+        snippets.truncate(page_size.max(0) as usize);
+        rowids.truncate(page_size.max(0) as usize);
+        // Backward pages are fetched in descending order but shown ascending
+        if backward {
+            rows.reverse();
+            row_types.reverse();
+            snippets.reverse();
+            rowids.reverse();
+        }
+        has_more
+    } else {
+        page < total_pages
+    };
+
+    // For an empty page the cursors point back at the boundary it started from
+    let (next_cursor, prev_cursor) = match &row_cursor {
+        None => (None, None),
+        Some(start) => {
+            let start_rowid = start.as_ref().map(|c| c.rowid);
+            let next = if backward {
+                rowids
+                    .last()
+                    .copied()
+                    .or(start_rowid.map(|r| r.saturating_sub(1)))
+            } else if has_more {
+                rowids.last().copied()
+            } else {
+                None
+            };
+            let prev = if backward && !has_more {
+                None
+            } else if backward {
+                rowids.first().copied()
+            } else {
+                start_rowid.map(|r| rowids.first().copied().unwrap_or(r.saturating_add(1)))
+            };
+            (
+                next.map(|rowid| {
+                    RowCursor {
+                        rowid,
+                        backward: false,
+                    }
+                    .encode()
+                }),
+                prev.map(|rowid| {
+                    RowCursor {
+                        rowid,
+                        backward: true,
+                    }
+                    .encode()
+                }),
+            )
+        }
+    };
+
+    // Only applies to columns literally named after the rowid aliases
+    let (columns, rows, row_types) = if hide_rowid.unwrap_or(false) {
+        let keep: Vec<usize> = (0..columns.len())
+            .filter(|&i| {
+                !["rowid", "_rowid_", "oid"].contains(&columns[i].to_ascii_lowercase().as_str())
+            })
+            .collect();
+        let rows = rows
+            .into_iter()
+            .map(|row| keep.iter().map(|&i| row[i].clone()).collect())
+            .collect();
+        let row_types = row_types
+            .into_iter()
+            .map(|types| {
+                if types.is_empty() {
+                    types
+                } else {
+                    keep.iter().map(|&i| types[i].clone()).collect()
+                }
+            })
+            .collect();
+        (
+            keep.iter().map(|&i| columns[i].clone()).collect(),
+            rows,
+            row_types,
+        )
+    } else {
+        (columns, rows, row_types)
+    };
+
+    Ok(TableData {
+        columns,
+        rows,
+        total_pages,
+        clamped,
+        warnings,
+        has_more,
+        row_types: include_type_info.then_some(row_types),
+        change_token: Some(change_token),
+        data_changed,
+        is_fts,
+        snippets: fts_search.then_some(snippets),
+        next_cursor,
+        prev_cursor,
+    })
+}
+
+fn is_fts5_table(conn: &Connection, table: &str) -> Result<bool, String> {
+    let sql: Option<String> = conn
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            params![table],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten();
+    Ok(sql.is_some_and(|sql| {
+        sql.to_ascii_uppercase().starts_with("CREATE VIRTUAL TABLE")
+            && parse_virtual_table_sql(&sql).0.eq_ignore_ascii_case("fts5")
+    }))
+}
+
+// True when lookups on the column can use an index: it's the rowid alias or
+// leads at least one index
+fn is_indexed_column(conn: &Connection, table: &str, column: &str) -> Result<bool, String> {
+    let columns = get_table_columns(conn, table)?;
+    let pk_columns: Vec<&ColumnInfo> = columns.iter().filter(|c| c.primary_key > 0).collect();
+    if pk_columns.len() == 1
+        && pk_columns[0].name == column
+        && pk_columns[0].declared_type.eq_ignore_ascii_case("INTEGER")
+    {
+        return Ok(true);
+    }
+
+    conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM pragma_index_list(?1) il, pragma_index_info(il.name) ii WHERE ii.seqno = 0 AND ii.name = ?2)",
+        params![table, column],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+const DEFAULT_QUERY_TIMEOUT_MS: u64 = 30_000;
+
+// Aborts statements on this connection once the timeout has elapsed, returns the
+// timeout in effect so errors can report it
+fn set_query_timeout(conn: &Connection, timeout_ms: Option<u64>) -> Result<u64, String> {
+    // An explicit timeout also bounds lock waits, otherwise the stored busy_timeout applies
+    if let Some(ms) = timeout_ms {
+        conn.busy_timeout(std::time::Duration::from_millis(ms))
+            .map_err(|e| e.to_string())?;
+    }
+    let timeout_ms = timeout_ms.unwrap_or(DEFAULT_QUERY_TIMEOUT_MS);
+    let deadline = Instant::now() + std::time::Duration::from_millis(timeout_ms);
+    conn.progress_handler(1000, Some(move || Instant::now() > deadline));
+    Ok(timeout_ms)
+}
+
+// Registered for the lifetime of the guard so cancel_query can interrupt the
+// connection; the entry is removed again when the guard is dropped
+struct ActiveQuery<'a> {
+    state: &'a AppState,
+    query_id: Option<String>,
+    cancelled: Arc<AtomicBool>,
+}
+
+fn register_active_query<'a>(
+    state: &'a AppState,
+    query_id: Option<String>,
+    conn: &Connection,
+) -> ActiveQuery<'a> {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    if let Some(query_id) = &query_id {
+        let handle = conn.get_interrupt_handle();
+        let flag = cancelled.clone();
+        state
+            .active_queries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(
+                query_id.clone(),
+                Box::new(move || {
+                    flag.store(true, Ordering::SeqCst);
+                    handle.interrupt();
+                }),
+            );
+    }
+    ActiveQuery {
+        state,
+        query_id,
+        cancelled,
+    }
+}
+
+impl ActiveQuery<'_> {
+    // Timeouts and cancellation both interrupt the query, the flag tells them apart
+    fn map_error(&self, e: rusqlite::Error, timeout_ms: u64) -> String {
+        if self.cancelled.load(Ordering::SeqCst)
+            && e.sqlite_error_code() == Some(rusqlite::ErrorCode::OperationInterrupted)
+        {
+            "QueryCancelledError: query was cancelled".to_string()
+        } else {
+            map_timeout_error(e, timeout_ms)
+        }
+    }
+}
+
+impl Drop for ActiveQuery<'_> {
+    fn drop(&mut self) {
+        if let Some(query_id) = &self.query_id {
+            self.state
+                .active_queries
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(query_id);
+        }
+    }
+}
+
+#[tauri::command]
+async fn cancel_query(state: State<'_, AppState>, query_id: String) -> Result<(), String> {
+    if let Some(interrupt) = state
+        .active_queries
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&query_id)
+    {
+        interrupt();
+    }
+    Ok(())
+}
+
+fn map_timeout_error(e: rusqlite::Error, timeout_ms: u64) -> String {
+    if e.sqlite_error_code() == Some(rusqlite::ErrorCode::OperationInterrupted) {
+        format!("QueryTimeoutError: query exceeded {} ms", timeout_ms)
+    } else {
+        e.to_string()
+    }
+}
+
+// Accepts the ISO 8601 shapes SQLite's date functions understand: a plain date,
+// a date-time with `T` or space separator, and RFC 3339 with an offset
+fn is_iso8601(value: &str) -> bool {
+    parse_iso8601(value).is_some()
+}
+
+// Offsets are converted to UTC, dates without a time are taken as midnight
+fn parse_iso8601(value: &str) -> Option<chrono::NaiveDateTime> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|d| d.naive_utc())
+        .or_else(|_| {
+            chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map(|d| d.and_hms_opt(0, 0, 0).unwrap_or_default())
+        })
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f"))
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f"))
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M"))
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M"))
+        .ok()
+}
+
+// Builds a `%needle%` LIKE pattern that matches the needle literally; use it
+// together with `ESCAPE '\'` so % and _ in the needle aren't wildcards
+fn like_contains_pattern(needle: &str) -> String {
+    format!(
+        "%{}%",
+        needle
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    )
+}
+
+// Bound rather than spliced in, each ? takes its own copy of the pattern. % and _
+// typed by the user stay LIKE wildcards, unlike like_contains_pattern
+fn table_search_condition(
+    columns: &[String],
+    search: &str,
+) -> (String, Vec<rusqlite::types::Value>) {
+    let pattern = format!("%{}%", search);
+    let search_parts: Vec<String> = columns
+        .iter()
+        .map(|col| format!("{} LIKE ?", quote_identifier(col)))
+        .collect();
+    let search_params = columns
+        .iter()
+        .map(|_| rusqlite::types::Value::Text(pattern.clone()))
+        .collect();
+    (format!("({})", search_parts.join(" OR ")), search_params)
+}
+
+fn row_to_json_values(
+    row: &rusqlite::Row,
+    col_count: usize,
+) -> rusqlite::Result<Vec<serde_json::Value>> {
+    let mut row_values = Vec::with_capacity(col_count);
+    for i in 0..col_count {
+        let val: rusqlite::types::Value = row.get(i)?;
+        row_values.push(sqlite_value_to_json(val));
+    }
+    Ok(row_values)
+}
+
+fn row_to_type_names(row: &rusqlite::Row, col_count: usize) -> rusqlite::Result<Vec<String>> {
+    use rusqlite::types::Type;
+    let mut types = Vec::with_capacity(col_count);
+    for i in 0..col_count {
+        let name = match row.get_ref(i)?.data_type() {
+            Type::Null => "null",
+            Type::Integer => "integer",
+            Type::Real => "real",
+            Type::Text => "text",
+            Type::Blob => "blob",
+        };
+        types.push(name.to_string());
+    }
+    Ok(types)
+}
+
+fn sqlite_value_to_json(val: rusqlite::types::Value) -> serde_json::Value {
+    match val {
+        rusqlite::types::Value::Null => serde_json::Value::Null,
+        rusqlite::types::Value::Integer(i) => serde_json::Value::Number(i.into()),
+        rusqlite::types::Value::Real(f) => serde_json::Number::from_f64(f)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        rusqlite::types::Value::Text(t) => serde_json::Value::String(t),
+        rusqlite::types::Value::Blob(b) => {
+            serde_json::Value::String(format!("<{} bytes>", b.len()))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_db_stats(state: State<'_, AppState>, path: String) -> Result<DbStats, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+
+    let mut stmt = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%'")
+        .map_err(|e| e.to_string())?;
+    let table_names: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut total_records = 0;
+    for table in &table_names {
+        let count: i64 = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM {}", quote_identifier(table)),
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(0);
+        total_records += count;
+    }
+
+    let file_metadata = std::fs::metadata(&path).map_err(|e| e.to_string())?;
+    let file_size_kb = file_metadata.len() / 1024;
+
+    let (page_size, fragmentation_percent) = match get_page_info(&conn) {
+        Some((page_size, fragmentation)) => (Some(page_size), Some(fragmentation)),
+        None => (None, None),
+    };
+
+    Ok(DbStats {
+        total_tables: table_names.len(),
+        total_records,
+        file_size_kb,
+        page_size,
+        fragmentation_percent,
+        encoding: get_db_encoding(&conn)?,
+        journal_mode: get_journal_mode(&conn)?,
+    })
+}
+
+fn get_journal_mode(conn: &Connection) -> Result<String, String> {
+    conn.query_row("PRAGMA journal_mode", [], |row| row.get(0))
+        .map_err(|e| e.to_string())
+}
+
+const JOURNAL_MODES: [&str; 6] = ["DELETE", "TRUNCATE", "PERSIST", "MEMORY", "WAL", "OFF"];
+
+#[tauri::command]
+async fn set_journal_mode(
+    state: State<'_, AppState>,
+    path: String,
+    mode: String,
+) -> Result<String, String> {
+    let mode = mode.to_uppercase();
+    if !JOURNAL_MODES.contains(&mode.as_str()) {
+        return Err(format!(
+            "Unsupported journal mode '{}', expected one of {}",
+            mode,
+            JOURNAL_MODES.join(", ")
+        ));
+    }
+
+    let conn = open_read_write(&state, &path)?;
+    // SQLite answers with the mode actually in effect, which can differ from the request
+    let active: String = conn
+        .query_row(&format!("PRAGMA journal_mode = {}", mode), [], |row| {
+            row.get(0)
+        })
+        .map_err(map_write_error)?;
+    Ok(active.to_uppercase())
+}
+
+fn get_db_encoding(conn: &Connection) -> Result<String, String> {
+    conn.query_row("PRAGMA encoding", [], |row| row.get(0))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_encoding(state: State<'_, AppState>, path: String) -> Result<String, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    get_db_encoding(&conn)
+}
+
+// Page size and the share of pages sitting on the freelist, or `None` when
+// the pragmas can't be read (e.g. a freshly created empty file).
+fn get_page_info(conn: &Connection) -> Option<(u32, f64)> {
+    let page_size: u32 = conn
+        .query_row("PRAGMA page_size", [], |row| row.get(0))
+        .ok()?;
+    let page_count: u64 = conn
+        .query_row("PRAGMA page_count", [], |row| row.get(0))
+        .ok()?;
+    let freelist_count: u64 = conn
+        .query_row("PRAGMA freelist_count", [], |row| row.get(0))
+        .ok()?;
+
+    if page_count == 0 {
+        return None;
+    }
+
+    let fragmentation =
+        (freelist_count as f64 * page_size as f64 * 100.0) / (page_count as f64 * page_size as f64);
+    Some((page_size, fragmentation))
+}
+
+#[tauri::command]
+async fn copy_table(
+    state: State<'_, AppState>,
+    path: String,
+    source_table: String,
+    dest_table: String,
+    copy_data: bool,
+) -> Result<u64, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+
+    if !table_exists(&conn, &source_table)? {
+        return Err(format!("Table '{}' does not exist", source_table));
+    }
+    if table_exists(&conn, &dest_table)? {
+        return Err(format!("Table '{}' already exists", dest_table));
+    }
+
+    // WHERE 0 keeps the column layout but copies no rows
+    let filter = if copy_data { "" } else { " WHERE 0" };
+    conn.execute(
+        &format!(
+            "CREATE TABLE {} AS SELECT * FROM {}{}",
+            quote_identifier(&dest_table),
+            quote_identifier(&source_table),
+            filter
+        ),
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let copied: u64 = conn
+        .query_row(
+            &format!("SELECT COUNT(*) FROM {}", quote_identifier(&dest_table)),
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(copied)
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool, String> {
+    let count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name = ?1",
+            params![table],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(count > 0)
+}
+
+#[tauri::command]
+async fn list_virtual_tables(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<Vec<VirtualTableInfo>, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let mut stmt = conn
+        .prepare("SELECT name, sql FROM sqlite_master WHERE type='table' AND sql LIKE 'CREATE VIRTUAL TABLE%'")
+        .map_err(|e| e.to_string())?;
+    let rows: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut tables = Vec::new();
+    for (name, sql) in rows {
+        let (module, args) = parse_virtual_table_sql(&sql);
+        tables.push(VirtualTableInfo { name, module, args });
+    }
+    Ok(tables)
+}
+
+// Splits `CREATE VIRTUAL TABLE x USING module(args)` into the module name and raw args
+fn parse_virtual_table_sql(sql: &str) -> (String, String) {
+    let upper = sql.to_ascii_uppercase();
+    let Some(using_pos) = upper.find(" USING ") else {
+        return (String::new(), String::new());
+    };
+    let rest = sql[using_pos + " USING ".len()..].trim();
+
+    match rest.find('(') {
+        Some(open) => {
+            let module = rest[..open].trim().to_string();
+            let close = rest.rfind(')').unwrap_or(rest.len());
+            let args = if close > open {
+                rest[open + 1..close].trim().to_string()
+            } else {
+                String::new()
+            };
+            (module, args)
+        }
+        None => (rest.trim_end_matches(';').trim().to_string(), String::new()),
+    }
+}
+
+#[tauri::command]
+async fn full_text_search(
+    state: State<'_, AppState>,
+    path: String,
+    fts_table: String,
+    query: String,
+    limit: Option<u32>,
+) -> Result<FtsResult, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let limit = limit.unwrap_or(100);
+
+    let total_matches: u64 = conn
+        .query_row(
+            &format!(
+                "SELECT COUNT(*) FROM {0} WHERE {0} MATCH ?1",
+                quote_identifier(&fts_table)
+            ),
+            params![query],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT * FROM {0} WHERE {0} MATCH ?1 LIMIT ?2",
+            quote_identifier(&fts_table)
+        ))
+        .map_err(|e| e.to_string())?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
+
+    let rows = stmt
+        .query_map(params![query, limit], |row| {
+            let mut map = HashMap::new();
+            for (i, col) in columns.iter().enumerate() {
+                let val: rusqlite::types::Value = row.get(i)?;
+                map.insert(col.clone(), sqlite_value_to_json(val));
+            }
+            Ok(map)
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(FtsResult {
+        rows,
+        total_matches,
+    })
+}
+
+#[tauri::command]
+async fn fts_snippet(
+    state: State<'_, AppState>,
+    path: String,
+    fts_table: String,
+    query: String,
+    column: String,
+    limit: Option<u32>,
+) -> Result<Vec<String>, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let limit = limit.unwrap_or(100);
+
+    // snippet() addresses columns by position, not by name
+    let columns = get_column_names(&conn, &fts_table)?;
+    let column_index = columns
+        .iter()
+        .position(|c| c == &column)
+        .ok_or_else(|| format!("Column '{}' not found in '{}'", column, fts_table))?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT snippet({0}, ?1, '<mark>', '</mark>', '...', 16) FROM {0} WHERE {0} MATCH ?2 LIMIT ?3",
+            quote_identifier(&fts_table)
+        ))
+        .map_err(|e| e.to_string())?;
+    let snippets = stmt
+        .query_map(params![column_index as i64, query, limit], |row| {
+            row.get::<_, Option<String>>(0)
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(snippets
+        .into_iter()
+        .map(|s| s.unwrap_or_default())
+        .collect())
+}
+
+// Runs SQLite's own ANALYZE, which refreshes the sqlite_stat* tables the query
+// planner uses to pick indexes. This is unrelated to start_db_analysis, which
+// computes the app's character/format statistics and never touches the file.
+#[tauri::command]
+async fn analyze_table(
+    state: State<'_, AppState>,
+    path: String,
+    table: Option<String>,
+) -> Result<(), String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let sql = match table {
+        Some(table) => format!("ANALYZE {}", quote_identifier(&table)),
+        None => "ANALYZE".to_string(),
+    };
+    conn.execute_batch(&sql).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn validate_sql(
+    state: State<'_, AppState>,
+    path: String,
+    sql: String,
+) -> Result<ValidationResult, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+
+    // prepare only parses and plans the statement, nothing is executed
+    let result = match conn.prepare(&sql) {
+        Ok(_) => ValidationResult {
+            is_valid: true,
+            error_message: None,
+            error_offset: None,
+        },
+        Err(rusqlite::Error::SqlInputError {
+            error, msg, offset, ..
+        }) => ValidationResult {
+            is_valid: false,
+            error_message: Some(format!("{} ({:?})", msg, error.code)),
+            error_offset: usize::try_from(offset).ok(),
+        },
+        Err(rusqlite::Error::SqliteFailure(error, msg)) => ValidationResult {
+            is_valid: false,
+            error_message: Some(match msg {
+                Some(msg) => format!("{} ({:?})", msg, error.code),
+                None => error.to_string(),
+            }),
+            error_offset: None,
+        },
+        Err(e) => ValidationResult {
+            is_valid: false,
+            error_message: Some(e.to_string()),
+            error_offset: None,
+        },
+    };
+
+    Ok(result)
+}
+
+#[tauri::command]
+async fn get_analyze_statistics(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<Vec<SqliteStat1Row>, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+
+    // sqlite_stat1 only exists once ANALYZE has been run
+    if !table_exists(&conn, "sqlite_stat1")? {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT tbl, idx, stat FROM sqlite_stat1 ORDER BY tbl, idx")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(SqliteStat1Row {
+                tbl: row.get(0)?,
+                // idx is NULL for the whole-table row of tables without indexes
+                idx: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                stat: row.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+#[tauri::command]
+async fn get_index_usage_stats(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<Option<Vec<SqliteStat4Row>>, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+
+    // sqlite_stat4 is only written by ANALYZE in builds with SQLITE_ENABLE_STAT4
+    if !table_exists(&conn, "sqlite_stat4")? {
+        return Ok(None);
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT tbl, idx, neq, nlt, ndlt, sample FROM sqlite_stat4 ORDER BY tbl, idx")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(SqliteStat4Row {
+                tbl: row.get(0)?,
+                idx: row.get(1)?,
+                neq: row.get(2)?,
+                nlt: row.get(3)?,
+                ndlt: row.get(4)?,
+                sample: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(Some(rows))
+}
+
+// REINDEX writes to the file, so open read-write (without create) and report
+// read-only files clearly instead of surfacing a bare SQLite error.
+fn open_read_write(state: &AppState, path: &str) -> Result<Connection, String> {
+    open_database_with_flags(
+        state,
+        path,
+        OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+}
+
+fn map_write_error(e: rusqlite::Error) -> String {
+    match e.sqlite_error_code() {
+        Some(rusqlite::ErrorCode::ReadOnly) | Some(rusqlite::ErrorCode::CannotOpen) => {
+            format!("Database is not writable: {}", e)
+        }
+        _ => e.to_string(),
+    }
+}
+
+#[tauri::command]
+async fn reindex_table(
+    state: State<'_, AppState>,
+    path: String,
+    table: Option<String>,
+) -> Result<(), String> {
+    let conn = open_read_write(&state, &path)?;
+    let sql = match table {
+        Some(table) => {
+            if !table_exists(&conn, &table)? {
+                return Err(format!("Table '{}' does not exist", table));
+            }
+            format!("REINDEX {}", quote_identifier(&table))
+        }
+        None => "REINDEX".to_string(),
+    };
+    conn.execute_batch(&sql).map_err(map_write_error)?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn reindex_index(
+    state: State<'_, AppState>,
+    path: String,
+    index_name: String,
+) -> Result<(), String> {
+    let conn = open_read_write(&state, &path)?;
+    let exists: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type='index' AND name = ?1",
+            params![index_name],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if exists == 0 {
+        return Err(format!("Index '{}' does not exist", index_name));
+    }
+
+    conn.execute_batch(&format!("REINDEX {}", quote_identifier(&index_name)))
+        .map_err(map_write_error)?;
+    Ok(())
+}
+
+enum ParquetColumn {
+    Int64(Int64Builder),
+    Double(Float64Builder),
+    Utf8(StringBuilder),
+    Binary(BinaryBuilder),
+}
+
+impl ParquetColumn {
+    fn for_affinity(affinity: &str) -> Self {
+        match affinity {
+            "INTEGER" => ParquetColumn::Int64(Int64Builder::new()),
+            "REAL" | "NUMERIC" => ParquetColumn::Double(Float64Builder::new()),
+            "TEXT" => ParquetColumn::Utf8(StringBuilder::new()),
+            _ => ParquetColumn::Binary(BinaryBuilder::new()),
+        }
+    }
+
+    fn data_type(&self) -> DataType {
+        match self {
+            ParquetColumn::Int64(_) => DataType::Int64,
+            ParquetColumn::Double(_) => DataType::Float64,
+            ParquetColumn::Utf8(_) => DataType::Utf8,
+            ParquetColumn::Binary(_) => DataType::Binary,
+        }
+    }
+
+    // SQLite doesn't enforce declared types, so values that don't fit the
+    // column's Parquet type are coerced where sensible and written as null otherwise
+    fn append(&mut self, value: rusqlite::types::Value) {
+        use rusqlite::types::Value;
+        match self {
+            ParquetColumn::Int64(b) => b.append_option(match value {
+                Value::Integer(i) => Some(i),
+                Value::Real(f) => Some(f as i64),
+                Value::Text(t) => t.trim().parse().ok(),
+                _ => None,
+            }),
+            ParquetColumn::Double(b) => b.append_option(match value {
+                Value::Integer(i) => Some(i as f64),
+                Value::Real(f) => Some(f),
+                Value::Text(t) => t.trim().parse().ok(),
+                _ => None,
+            }),
+            ParquetColumn::Utf8(b) => b.append_option(match value {
+                Value::Integer(i) => Some(i.to_string()),
+                Value::Real(f) => Some(f.to_string()),
+                Value::Text(t) => Some(t),
+                Value::Blob(bytes) => Some(String::from_utf8_lossy(&bytes).into_owned()),
+                Value::Null => None,
+            }),
+            ParquetColumn::Binary(b) => b.append_option(match value {
+                Value::Integer(i) => Some(i.to_string().into_bytes()),
+                Value::Real(f) => Some(f.to_string().into_bytes()),
+                Value::Text(t) => Some(t.into_bytes()),
+                Value::Blob(bytes) => Some(bytes),
+                Value::Null => None,
+            }),
+        }
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            ParquetColumn::Int64(b) => Arc::new(b.finish()),
+            ParquetColumn::Double(b) => Arc::new(b.finish()),
+            ParquetColumn::Utf8(b) => Arc::new(b.finish()),
+            ParquetColumn::Binary(b) => Arc::new(b.finish()),
+        }
+    }
+}
+
+#[tauri::command]
+async fn export_table_to_parquet(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+    output_path: String,
+) -> Result<u64, String> {
+    const BATCH_SIZE: usize = 1000;
+
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let columns = get_table_columns(&conn, &table)?;
+    if columns.is_empty() {
+        return Err(format!("Table '{}' does not exist", table));
+    }
+
+    let mut builders: Vec<ParquetColumn> = columns
+        .iter()
+        .map(|c| ParquetColumn::for_affinity(column_affinity(&c.declared_type)))
+        .collect();
+    let schema = Arc::new(Schema::new(
+        columns
+            .iter()
+            .zip(&builders)
+            .map(|(c, b)| Field::new(&c.name, b.data_type(), true))
+            .collect::<Vec<_>>(),
+    ));
+
+    let file = std::fs::File::create(&output_path).map_err(|e| e.to_string())?;
+    let mut writer = ArrowWriter::try_new(file, schema.clone(), None).map_err(|e| e.to_string())?;
+
+    let select = columns
+        .iter()
+        .map(|c| quote_identifier(&c.name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM {}",
+            select,
+            quote_identifier(&table)
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+
+    let mut written: u64 = 0;
+    let mut pending = 0;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        for (i, builder) in builders.iter_mut().enumerate() {
+            let value: rusqlite::types::Value = row.get(i).map_err(|e| e.to_string())?;
+            builder.append(value);
+        }
+        pending += 1;
+
+        if pending == BATCH_SIZE {
+            write_parquet_batch(&mut writer, &schema, &mut builders)?;
+            written += pending as u64;
+            pending = 0;
+        }
+    }
+    if pending > 0 {
+        write_parquet_batch(&mut writer, &schema, &mut builders)?;
+        written += pending as u64;
+    }
+
+    writer.close().map_err(|e| e.to_string())?;
+    Ok(written)
+}
+
+fn write_parquet_batch(
+    writer: &mut ArrowWriter<std::fs::File>,
+    schema: &Arc<Schema>,
+    builders: &mut [ParquetColumn],
+) -> Result<(), String> {
+    let arrays = builders.iter_mut().map(|b| b.finish()).collect();
+    let batch = RecordBatch::try_new(schema.clone(), arrays).map_err(|e| e.to_string())?;
+    writer.write(&batch).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn compute_cross_table_join_preview(
+    state: State<'_, AppState>,
+    path: String,
+    left_table: String,
+    right_table: String,
+    left_col: String,
+    right_col: String,
+    join_type: String,
+    limit: u32,
+) -> Result<TableData, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+
+    let left_columns = get_column_names(&conn, &left_table)?;
+    let right_columns = get_column_names(&conn, &right_table)?;
+    if !left_columns.contains(&left_col) {
+        return Err(format!(
+            "Column '{}' does not exist in '{}'",
+            left_col, left_table
+        ));
+    }
+    if !right_columns.contains(&right_col) {
+        return Err(format!(
+            "Column '{}' does not exist in '{}'",
+            right_col, right_table
+        ));
+    }
+
+    // Aliases keep self-joins unambiguous; output columns are named table.column
+    let mut select_parts = Vec::new();
+    let mut columns = Vec::new();
+    for (alias, table, table_columns) in [
+        ("l", &left_table, &left_columns),
+        ("r", &right_table, &right_columns),
+    ] {
+        for col in table_columns {
+            select_parts.push(format!("{}.{}", alias, quote_identifier(col)));
+            columns.push(format!("{}.{}", table, col));
+        }
+    }
+
+    // RIGHT JOIN is emulated by swapping the sides of a LEFT JOIN, the select
+    // list above still keeps the left table's columns first
+    let from_clause = match join_type.to_uppercase().as_str() {
+        "INNER" => format!(
+            "{} AS l INNER JOIN {} AS r",
+            quote_identifier(&left_table),
+            quote_identifier(&right_table)
+        ),
+        "LEFT" => format!(
+            "{} AS l LEFT JOIN {} AS r",
+            quote_identifier(&left_table),
+            quote_identifier(&right_table)
+        ),
+        "RIGHT" => format!(
+            "{} AS r LEFT JOIN {} AS l",
+            quote_identifier(&right_table),
+            quote_identifier(&left_table)
+        ),
+        other => {
+            return Err(format!(
+                "Unsupported join type '{}', expected INNER, LEFT or RIGHT",
+                other
+            ))
+        }
+    };
+
+    let query = format!(
+        "SELECT {} FROM {} ON l.{} = r.{} LIMIT ?1",
+        select_parts.join(", "),
+        from_clause,
+        quote_identifier(&left_col),
+        quote_identifier(&right_col)
+    );
+    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let col_count = stmt.column_count();
+    let rows = stmt
+        .query_map(params![limit], |row| row_to_json_values(row, col_count))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(TableData {
+        columns,
+        rows,
+        total_pages: 1,
+        clamped: false,
+        warnings: Vec::new(),
+        has_more: false,
+        row_types: None,
+        change_token: None,
+        data_changed: false,
+        is_fts: false,
+        snippets: None,
+        next_cursor: None,
+        prev_cursor: None,
+    })
+}
+
+// A column matches when its declared type contains type_affinity, ignoring case
+#[tauri::command]
+async fn find_columns_by_type(
+    state: State<'_, AppState>,
+    path: String,
+    type_affinity: String,
+) -> Result<Vec<ColumnLocation>, String> {
+    let affinity = type_affinity.to_ascii_uppercase();
+    if !["INTEGER", "REAL", "TEXT", "BLOB", "NUMERIC"].contains(&affinity.as_str()) {
+        return Err(format!(
+            "Unsupported type affinity '{}', expected INTEGER, REAL, TEXT, BLOB or NUMERIC",
+            type_affinity
+        ));
+    }
+
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let mut stmt = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name")
+        .map_err(|e| e.to_string())?;
+    let tables: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut locations = Vec::new();
+    for table in tables {
+        for column in get_table_columns(&conn, &table)? {
+            if column
+                .declared_type
+                .to_ascii_uppercase()
+                .contains(affinity.as_str())
+            {
+                locations.push(ColumnLocation {
+                    table_name: table.clone(),
+                    column_name: column.name,
+                    declared_type: column.declared_type,
+                });
+            }
+        }
+    }
+    Ok(locations)
+}
+
+#[tauri::command]
+async fn search_across_all_tables(
+    state: State<'_, AppState>,
+    path: String,
+    needle: String,
+    limit_per_table: Option<u32>,
+) -> Result<Vec<CrossTableSearchResult>, String> {
+    const MAX_TOTAL_RESULTS: usize = 10_000;
+
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let limit = limit_per_table.unwrap_or(100);
+
+    let mut stmt = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%'")
+        .map_err(|e| e.to_string())?;
+    let tables: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let pattern = like_contains_pattern(&needle);
+
+    let mut results = Vec::new();
+    let mut total_results = 0;
+    for table in &tables {
+        let text_columns: Vec<String> = get_table_columns(&conn, table)?
+            .into_iter()
+            .filter(|c| column_affinity(&c.declared_type) == "TEXT")
+            .map(|c| c.name)
+            .collect();
+        if text_columns.is_empty() {
+            continue;
+        }
+
+        let conditions: Vec<String> = text_columns
+            .iter()
+            .map(|col| format!("{} LIKE ?1 ESCAPE '\\'", quote_identifier(col)))
+            .collect();
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT * FROM {} WHERE {} LIMIT ?2",
+                quote_identifier(table),
+                conditions.join(" OR ")
+            ))
+            .map_err(|e| e.to_string())?;
+        let column_names: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
+        let col_count = column_names.len();
+        let matching_rows = stmt
+            .query_map(params![pattern, limit], |row| {
+                row_to_json_values(row, col_count)
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        if matching_rows.is_empty() {
+            continue;
+        }
+
+        total_results += matching_rows.len();
+        results.push(CrossTableSearchResult {
+            table_name: table.clone(),
+            matching_rows,
+            column_names,
+        });
+
+        if total_results >= MAX_TOTAL_RESULTS {
+            break;
+        }
+    }
+
+    Ok(results)
+}
+
+#[tauri::command]
+async fn get_auto_increment_state(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<Vec<AutoIncrementState>, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+
+    // sqlite_sequence is only created once a table uses AUTOINCREMENT
+    if !table_exists(&conn, "sqlite_sequence")? {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT name, seq FROM sqlite_sequence ORDER BY name")
+        .map_err(|e| e.to_string())?;
+    let states = stmt
+        .query_map([], |row| {
+            Ok(AutoIncrementState {
+                table_name: row.get(0)?,
+                max_seq: row.get(1)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(states)
+}
+
+// WITHOUT ROWID tables have no rowid column, so they return an error instead of a range
+#[tauri::command]
+async fn get_rowid_range(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+) -> Result<RowidRange, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    if !table_exists(&conn, &table)? {
+        return Err(format!("Table '{}' does not exist", table));
+    }
+
+    // MIN/MAX are NULL for an empty table
+    conn.query_row(
+        &format!(
+            "SELECT MIN(rowid), MAX(rowid), COUNT(*) FROM {}",
+            quote_identifier(&table)
+        ),
+        [],
+        |row| {
+            Ok(RowidRange {
+                min_rowid: row.get(0)?,
+                max_rowid: row.get(1)?,
+                row_count: row.get(2)?,
+            })
+        },
+    )
+    .map_err(|e| map_rowid_error(e, &table))
+}
+
+fn map_rowid_error(e: rusqlite::Error, table: &str) -> String {
+    if e.to_string().contains("no such column: rowid") {
+        format!(
+            "Table '{}' is a WITHOUT ROWID table and has no rowid",
+            table
+        )
+    } else {
+        e.to_string()
+    }
+}
+
+#[tauri::command]
+async fn get_row_by_rowid(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+    rowid: i64,
+) -> Result<Option<HashMap<String, serde_json::Value>>, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    if !table_exists(&conn, &table)? {
+        return Err(format!("Table '{}' does not exist", table));
+    }
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT * FROM {} WHERE rowid = ?1",
+            quote_identifier(&table)
+        ))
+        .map_err(|e| map_rowid_error(e, &table))?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
+    stmt.query_row(params![rowid], |row| row_to_json_values(row, columns.len()))
+        .optional()
+        .map_err(|e| e.to_string())
+        .map(|values| values.map(|values| columns.into_iter().zip(values).collect()))
+}
+
+#[tauri::command]
+async fn copy_row(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+    source_rowid: i64,
+    overrides: HashMap<String, serde_json::Value>,
+) -> Result<i64, String> {
+    let conn = open_read_write(&state, &path)?;
+    if !table_exists(&conn, &table)? {
+        return Err(format!("Table '{}' does not exist", table));
+    }
+
+    // table_info leaves out generated columns, which can't be inserted into anyway
+    let table_columns = get_table_columns(&conn, &table)?;
+    if let Some(unknown) = overrides
+        .keys()
+        .find(|k| !table_columns.iter().any(|c| &c.name == *k))
+    {
+        return Err(format!(
+            "Column '{}' does not exist in '{}'",
+            unknown, table
+        ));
+    }
+
+    // An INTEGER PRIMARY KEY (the only kind AUTOINCREMENT allows) aliases the rowid,
+    // so copying it would always collide; leave it out and let SQLite assign one
+    let pk_columns: Vec<_> = table_columns.iter().filter(|c| c.primary_key > 0).collect();
+    let rowid_alias = match pk_columns.as_slice() {
+        [pk] if pk.declared_type.eq_ignore_ascii_case("INTEGER") => Some(pk.name.clone()),
+        _ => None,
+    };
+    let columns: Vec<&str> = table_columns
+        .iter()
+        .map(|c| c.name.as_str())
+        .filter(|name| Some(*name) != rowid_alias.as_deref() || overrides.contains_key(*name))
+        .collect();
+
+    let quoted_columns = columns
+        .iter()
+        .map(|c| quote_identifier(c))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut values: Vec<rusqlite::types::Value> = conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM {} WHERE rowid = ?1",
+                if columns.is_empty() {
+                    "1"
+                } else {
+                    &quoted_columns
+                },
+                quote_identifier(&table)
+            ),
+            params![source_rowid],
+            |row| (0..columns.len()).map(|i| row.get(i)).collect(),
+        )
+        .optional()
+        .map_err(|e| map_rowid_error(e, &table))?
+        .ok_or_else(|| format!("No row with rowid {} in '{}'", source_rowid, table))?;
+    for (i, column) in columns.iter().enumerate() {
+        if let Some(value) = overrides.get(*column) {
+            values[i] = json_to_sqlite_value(value);
+        }
+    }
+
+    // A table with nothing but its rowid alias has no values to copy
+    let insert_sql = if columns.is_empty() {
+        format!("INSERT INTO {} DEFAULT VALUES", quote_identifier(&table))
+    } else {
+        format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            quote_identifier(&table),
+            quoted_columns,
+            vec!["?"; columns.len()].join(", ")
+        )
+    };
+    conn.execute(&insert_sql, params_from_iter(values))
+        .map_err(map_write_error)?;
+    Ok(conn.last_insert_rowid())
+}
+
+// SQLite stores TEXT bytes as given, so values written by other tools can hold
+// invalid UTF-8. Only the raw bytes are read here, converting them to a Value panics
+#[tauri::command]
+async fn find_text_encoding_issues(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+) -> Result<Vec<EncodingIssue>, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    if !table_exists(&conn, &table)? {
+        return Err(format!("Table '{}' does not exist", table));
+    }
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT rowid, * FROM {}",
+            quote_identifier(&table)
+        ))
+        .map_err(|e| map_rowid_error(e, &table))?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
+    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+
+    let mut issues = Vec::new();
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let rowid: i64 = row.get(0).map_err(|e| e.to_string())?;
+        for (i, column) in columns.iter().enumerate().skip(1) {
+            if let Ok(rusqlite::types::ValueRef::Text(bytes)) = row.get_ref(i) {
+                if let Err(e) = std::str::from_utf8(bytes) {
+                    issues.push(EncodingIssue {
+                        row_rowid: rowid,
+                        column: column.clone(),
+                        byte_offset: e.valid_up_to(),
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(issues)
+}
+
+fn json_to_sqlite_value(value: &serde_json::Value) -> rusqlite::types::Value {
+    use rusqlite::types::Value;
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Integer(*b as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => Value::Text(s.clone()),
+        // Nested structures are kept as their JSON text
+        serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+            Value::Blob(value.to_string().into_bytes())
+        }
+    }
+}
+
+fn json_sqlite_type(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Number(n) if n.is_i64() => "INTEGER",
+        serde_json::Value::Number(_) => "REAL",
+        serde_json::Value::Bool(_) => "INTEGER",
+        serde_json::Value::Array(_) | serde_json::Value::Object(_) => "BLOB",
+        serde_json::Value::String(_) | serde_json::Value::Null => "TEXT",
+    }
+}
+
+#[tauri::command]
+async fn import_json_array_to_table(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+    json_path: String,
+    create_if_missing: bool,
+) -> Result<u64, String> {
+    let contents = std::fs::read_to_string(&json_path).map_err(|e| e.to_string())?;
+    let objects: Vec<serde_json::Map<String, serde_json::Value>> =
+        serde_json::from_str(&contents).map_err(|e| format!("Invalid JSON array: {}", e))?;
+    if objects.is_empty() {
+        return Ok(0);
+    }
+
+    let mut conn = open_read_write(&state, &path)?;
+    // The table creation and every insert share one transaction, so a bad row
+    // anywhere in the file leaves neither a new table nor part of the rows behind
+    let tx = conn.transaction().map_err(map_write_error)?;
+
+    if !table_exists(&tx, &table)? {
+        if !create_if_missing {
+            return Err(format!("Table '{}' does not exist", table));
+        }
+        let column_defs: Vec<String> = objects[0]
+            .iter()
+            .map(|(key, value)| format!("{} {}", quote_identifier(key), json_sqlite_type(value)))
+            .collect();
+        tx.execute(
+            &format!(
+                "CREATE TABLE {} ({})",
+                quote_identifier(&table),
+                column_defs.join(", ")
+            ),
+            [],
+        )
+        .map_err(map_write_error)?;
+    }
+
+    // Every key used anywhere in the file has to map onto a table column
+    let table_columns = get_column_names(&tx, &table)?;
+    let mut columns: Vec<String> = Vec::new();
+    for object in &objects {
+        for key in object.keys() {
+            if !columns.contains(key) {
+                if !table_columns.contains(key) {
+                    return Err(format!("Column '{}' does not exist in '{}'", key, table));
+                }
+                columns.push(key.clone());
+            }
+        }
+    }
+
+    let insert_sql = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        quote_identifier(&table),
+        columns
+            .iter()
+            .map(|c| quote_identifier(c))
+            .collect::<Vec<_>>()
+            .join(", "),
+        vec!["?"; columns.len()].join(", ")
+    );
+
+    let mut inserted: u64 = 0;
+    {
+        let mut stmt = tx.prepare(&insert_sql).map_err(|e| e.to_string())?;
+        for object in &objects {
+            let values = columns.iter().map(|col| {
+                object
+                    .get(col)
+                    .map(json_to_sqlite_value)
+                    .unwrap_or(rusqlite::types::Value::Null)
+            });
+            stmt.execute(params_from_iter(values))
+                .map_err(map_write_error)?;
+            inserted += 1;
+        }
+    }
+    tx.commit().map_err(map_write_error)?;
+
+    Ok(inserted)
+}
+
+#[tauri::command]
+async fn get_wal_info(state: State<'_, AppState>, path: String) -> Result<WalInfo, String> {
+    let wal_path = format!("{}-wal", path);
+    let wal_size_bytes = std::fs::metadata(&wal_path).ok().map(|m| m.len());
+
+    // A WAL file is a 32 byte header followed by frames of a 24 byte header plus one page
+    let wal_pages = match wal_size_bytes {
+        Some(size) => {
+            let conn = open_with_connection_prefs(&state, &path)?;
+            let page_size: u64 = conn
+                .query_row("PRAGMA page_size", [], |row| row.get(0))
+                .map_err(|e| e.to_string())?;
+            Some(size.saturating_sub(32) / (page_size + 24))
+        }
+        None => None,
+    };
+
+    let last_checkpoint_result = state
+        .wal_checkpoints
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&path)
+        .copied();
+
+    Ok(WalInfo {
+        wal_exists: wal_size_bytes.is_some(),
+        wal_size_bytes,
+        wal_pages,
+        last_checkpoint_result,
+    })
+}
+
+#[tauri::command]
+async fn checkpoint_wal(
+    state: State<'_, AppState>,
+    path: String,
+    mode: String,
+) -> Result<(i32, i32), String> {
+    let mode = mode.to_uppercase();
+    if !["FULL", "RESTART", "TRUNCATE"].contains(&mode.as_str()) {
+        return Err(format!(
+            "Unsupported checkpoint mode '{}', expected FULL, RESTART or TRUNCATE",
+            mode
+        ));
+    }
+
+    let conn = open_read_write(&state, &path)?;
+    // Returns (busy, frames in the WAL, frames copied back into the database)
+    let (busy, pages_written, pages_moved): (i32, i32, i32) = conn
+        .query_row(&format!("PRAGMA wal_checkpoint({})", mode), [], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })
+        .map_err(map_write_error)?;
+
+    state
+        .wal_checkpoints
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(path, busy);
+    Ok((pages_written, pages_moved))
+}
+
+#[tauri::command]
+async fn log_checkpoint(
+    state: State<'_, AppState>,
+    db_path: String,
+) -> Result<WalCheckpointStats, String> {
+    let conn = open_read_write(&state, &db_path)?;
+    // PASSIVE never waits on readers or writers, so it's safe to run periodically
+    let (busy, log, checkpointed): (i32, i32, i32) = conn
+        .query_row("PRAGMA wal_checkpoint(PASSIVE)", [], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })
+        .map_err(map_write_error)?;
+
+    let metadata_conn = get_metadata_conn(&state)?;
+    let timestamp: String = metadata_conn
+        .query_row(
+            "INSERT INTO wal_checkpoint_log (db_path, busy, log, checkpointed) VALUES (?1, ?2, ?3, ?4) RETURNING timestamp",
+            params![db_path, busy, log, checkpointed],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    state
+        .wal_checkpoints
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(db_path, busy);
+    Ok(WalCheckpointStats {
+        timestamp,
+        busy,
+        log,
+        checkpointed,
+    })
+}
+
+#[tauri::command]
+async fn list_checkpoint_stats(
+    state: State<'_, AppState>,
+    db_path: String,
+    limit: i64,
+) -> Result<Vec<WalCheckpointStats>, String> {
+    let conn = get_metadata_conn(&state)?;
+    let mut stmt = conn
+        .prepare("SELECT timestamp, busy, log, checkpointed FROM wal_checkpoint_log WHERE db_path = ?1 ORDER BY id DESC LIMIT ?2")
+        .map_err(|e| e.to_string())?;
+    let stats = stmt
+        .query_map(params![db_path, limit], |row| {
+            Ok(WalCheckpointStats {
+                timestamp: row.get(0)?,
+                busy: row.get(1)?,
+                log: row.get(2)?,
+                checkpointed: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(stats)
+}
+
+#[tauri::command]
+async fn export_table_to_excel(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+    output_path: String,
+) -> Result<u64, String> {
+    // Excel's hard limit, one row is taken by the header
+    const MAX_XLSX_ROWS: u64 = 1_048_576;
+
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let mut stmt = conn
+        .prepare(&format!("SELECT * FROM {}", quote_identifier(&table)))
+        .map_err(|e| e.to_string())?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
+
+    let xlsx_err = |e: XlsxError| e.to_string();
+    let mut workbook = Workbook::new();
+    // Sheet names are capped at 31 chars and can't contain []:*?/\
+    let sheet_name: String = table
+        .chars()
+        .map(|c| if "[]:*?/\\".contains(c) { '_' } else { c })
+        .take(31)
+        .collect();
+    let sheet = workbook.add_worksheet();
+    sheet.set_name(&sheet_name).map_err(xlsx_err)?;
+
+    let bold = Format::new().set_bold();
+    for (i, col) in columns.iter().enumerate() {
+        sheet
+            .write_string_with_format(0, i as u16, col, &bold)
+            .map_err(xlsx_err)?;
+    }
+
+    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+    let mut written: u64 = 0;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        if written + 1 >= MAX_XLSX_ROWS {
+            return Err(format!(
+                "Table '{}' has more rows than an Excel sheet can hold",
+                table
+            ));
+        }
+        let xlsx_row = (written + 1) as u32;
+        for i in 0..columns.len() {
+            let value: rusqlite::types::Value = row.get(i).map_err(|e| e.to_string())?;
+            let col = i as u16;
+            match value {
+                rusqlite::types::Value::Null => continue,
+                rusqlite::types::Value::Integer(n) => sheet
+                    .write_number(xlsx_row, col, n as f64)
+                    .map_err(xlsx_err)?,
+                rusqlite::types::Value::Real(f) => {
+                    sheet.write_number(xlsx_row, col, f).map_err(xlsx_err)?
+                }
+                rusqlite::types::Value::Text(t) => {
+                    sheet.write_string(xlsx_row, col, &t).map_err(xlsx_err)?
+                }
+                rusqlite::types::Value::Blob(_) => sheet
+                    .write_string(xlsx_row, col, "[binary]")
+                    .map_err(xlsx_err)?,
+            };
+        }
+        written += 1;
+    }
+
+    workbook.save(&output_path).map_err(xlsx_err)?;
+    Ok(written)
+}
+
+// One JSON object per line and no surrounding array, so each line parses on its own
+#[tauri::command]
+async fn export_table_to_ndjson(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+    output_path: String,
+) -> Result<u64, String> {
+    use base64::Engine;
+
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let mut stmt = conn
+        .prepare(&format!("SELECT * FROM {}", quote_identifier(&table)))
+        .map_err(|e| e.to_string())?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
+
+    let file = std::fs::File::create(&output_path).map_err(|e| e.to_string())?;
+    let mut writer = std::io::BufWriter::new(file);
+    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+    let mut written: u64 = 0;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let mut object = serde_json::Map::with_capacity(columns.len());
+        for (i, col) in columns.iter().enumerate() {
+            let value = match row
+                .get::<_, rusqlite::types::Value>(i)
+                .map_err(|e| e.to_string())?
+            {
+                rusqlite::types::Value::Blob(b) => {
+                    serde_json::Value::String(base64::engine::general_purpose::STANDARD.encode(b))
+                }
+                value => sqlite_value_to_json(value),
+            };
+            object.insert(col.clone(), value);
+        }
+        serde_json::to_writer(&mut writer, &object).map_err(|e| e.to_string())?;
+        writer.write_all(b"\n").map_err(|e| e.to_string())?;
+        written += 1;
+    }
+
+    writer.flush().map_err(|e| e.to_string())?;
+    Ok(written)
+}
+
+// One year; also keeps interval_hours * 3600 and the first deadline in range
+const MAX_SCHEDULE_INTERVAL_HOURS: u64 = 24 * 365;
+
+#[tauri::command]
+async fn schedule_analysis(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    db_path: String,
+    interval_hours: u64,
+) -> Result<(), String> {
+    if interval_hours == 0 {
+        return Err("Interval must be at least one hour".into());
+    }
+    if interval_hours > MAX_SCHEDULE_INTERVAL_HOURS {
+        return Err(format!(
+            "Interval can be at most {} hours",
+            MAX_SCHEDULE_INTERVAL_HOURS
+        ));
+    }
+
+    let conn = get_metadata_conn(&state)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO scheduled_analyses (db_path, interval_hours) VALUES (?1, ?2)",
+        params![db_path, interval_hours as i64],
+    )
+    .map_err(|e| e.to_string())?;
+
+    spawn_scheduled_analysis(app, db_path, interval_hours);
+    Ok(())
+}
+
+#[tauri::command]
+async fn cancel_scheduled_analysis(
+    state: State<'_, AppState>,
+    db_path: String,
+) -> Result<(), String> {
+    let conn = get_metadata_conn(&state)?;
+    conn.execute(
+        "DELETE FROM scheduled_analyses WHERE db_path = ?1",
+        params![db_path],
+    )
+    .map_err(|e| e.to_string())?;
+
+    if let Some(token) = state
+        .scheduled_analyses
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&db_path)
+    {
+        token.store(true, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn list_scheduled_analyses(
+    state: State<'_, AppState>,
+) -> Result<Vec<ScheduledAnalysis>, String> {
+    let conn = get_metadata_conn(&state)?;
+    load_scheduled_analyses(&conn)
+}
+
+fn load_scheduled_analyses(conn: &Connection) -> Result<Vec<ScheduledAnalysis>, String> {
+    let mut stmt = conn
+        .prepare("SELECT db_path, interval_hours, created_at, last_run_at FROM scheduled_analyses ORDER BY created_at")
+        .map_err(|e| e.to_string())?;
+    let schedules = stmt
+        .query_map([], |row| {
+            Ok(ScheduledAnalysis {
+                db_path: row.get(0)?,
+                interval_hours: row.get::<_, i64>(1)?.max(1) as u64,
+                created_at: row.get(2)?,
+                last_run_at: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(schedules)
+}
+
+// Re-runs the analysis for db_path every interval_hours until the schedule is
+// cancelled or replaced. The first run happens one interval from now.
+fn spawn_scheduled_analysis(app: tauri::AppHandle, db_path: String, interval_hours: u64) {
+    // Saved rows predating the upper bound are skipped rather than panicking at startup
+    let Some((period, start)) = schedule_period(interval_hours) else {
+        return;
+    };
+
+    let token = Arc::new(AtomicBool::new(false));
+    {
+        let state = app.state::<AppState>();
+        let mut schedules = state
+            .scheduled_analyses
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some(old_token) = schedules.insert(db_path.clone(), token.clone()) {
+            old_token.store(true, Ordering::SeqCst);
+        }
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval_at(start, period);
+
+        loop {
+            interval.tick().await;
+            if token.load(Ordering::SeqCst) {
+                break;
+            }
+
+            let state = app.state::<AppState>();
+            let metadata_db_path = state.metadata_db_path.clone();
+            let cancellation_token = register_analysis_task(&state, &db_path);
+            let chunk_size = analysis_chunk_size(&metadata_db_path);
+            analyze_and_store(
+                &app,
+                &db_path,
+                cancellation_token,
+                chunk_size,
+                &metadata_db_path,
+            )
+            .await;
+
+            if let Ok(conn) = Connection::open(&metadata_db_path) {
+                let _ = conn.execute(
+                    "UPDATE scheduled_analyses SET last_run_at = CURRENT_TIMESTAMP WHERE db_path = ?1",
+                    params![db_path],
+                );
+            }
+        }
+    });
+}
+
+fn schedule_period(interval_hours: u64) -> Option<(std::time::Duration, tokio::time::Instant)> {
+    let period = std::time::Duration::from_secs(interval_hours.checked_mul(3600)?);
+    let start = tokio::time::Instant::now().checked_add(period)?;
+    Some((period, start))
+}
+
+#[tauri::command]
+async fn diff_table_data(
+    state: State<'_, AppState>,
+    path_a: String,
+    path_b: String,
+    table: String,
+    key_column: String,
+    limit: Option<u32>,
+) -> Result<TableDiff, String> {
+    // ATTACH creates a missing file, which would diff as every row removed
+    if !Path::new(&path_b).is_file() {
+        return Err(format!("Database file '{}' does not exist", path_b));
+    }
+    let conn = open_with_connection_prefs(&state, &path_a)?;
+    conn.execute("ATTACH DATABASE ?1 AS diff_b", params![path_b])
+        .map_err(|e| e.to_string())?;
+    let limit = limit.unwrap_or(100);
+
+    let columns = get_column_names(&conn, &table)?;
+    if columns.is_empty() {
+        return Err(format!(
+            "Table '{}' does not exist in the first database",
+            table
+        ));
+    }
+    let key_index = columns
+        .iter()
+        .position(|c| c == &key_column)
+        .ok_or_else(|| format!("Column '{}' does not exist in '{}'", key_column, table))?;
+
+    let columns_b: Vec<String> = conn
+        .prepare(&format!(
+            "PRAGMA diff_b.table_info({})",
+            quote_identifier(&table)
+        ))
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| row.get::<_, String>(1))?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| e.to_string())?;
+    if columns_b.is_empty() {
+        return Err(format!(
+            "Table '{}' does not exist in the second database",
+            table
+        ));
+    }
+    if let Some(missing) = columns.iter().find(|c| !columns_b.contains(c)) {
+        return Err(format!(
+            "Column '{}' is missing from '{}' in the second database",
+            missing, table
+        ));
+    }
+
+    let table_a = format!("main.{}", quote_identifier(&table));
+    let table_b = format!("diff_b.{}", quote_identifier(&table));
+    let key = quote_identifier(&key_column);
+    let column_list = columns
+        .iter()
+        .map(|c| quote_identifier(c))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let fetch_rows = |sql: &str, width: usize| -> Result<Vec<Vec<serde_json::Value>>, String> {
+        let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![limit], |row| row_to_json_values(row, width))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        Ok(rows)
+    };
+
+    // Keys present on one side only
+    let only_in = |from: &str, other: &str| {
+        format!(
+            "SELECT {cols} FROM {from} WHERE {key} IN (SELECT {key} FROM {from} EXCEPT SELECT {key} FROM {other}) LIMIT ?1",
+            cols = column_list,
+            from = from,
+            other = other,
+            key = key
+        )
+    };
+    let rows_only_in_a = fetch_rows(&only_in(&table_a, &table_b), columns.len())?;
+    let rows_only_in_b = fetch_rows(&only_in(&table_b, &table_a), columns.len())?;
+
+    // Keys on both sides whose full rows differ, fetched side by side
+    let side_by_side = columns
+        .iter()
+        .map(|c| format!("a.{}", quote_identifier(c)))
+        .chain(columns.iter().map(|c| format!("b.{}", quote_identifier(c))))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let changed_sql = format!(
+        "SELECT {side_by_side} FROM {a} AS a JOIN {b} AS b ON a.{key} = b.{key} \
+         WHERE a.{key} IN (SELECT {key} FROM (SELECT {cols} FROM {a} EXCEPT SELECT {cols} FROM {b})) LIMIT ?1",
+        side_by_side = side_by_side,
+        a = table_a,
+        b = table_b,
+        key = key,
+        cols = column_list
+    );
+    let rows_changed = fetch_rows(&changed_sql, columns.len() * 2)?
+        .into_iter()
+        .map(|mut old_values| {
+            let new_values = old_values.split_off(columns.len());
+            RowChange {
+                key_value: old_values[key_index].clone(),
+                old_values,
+                new_values,
+            }
+        })
+        .collect();
+
+    Ok(TableDiff {
+        columns,
+        rows_only_in_a,
+        rows_only_in_b,
+        rows_changed,
+    })
+}
+
+const AGGREGATE_FUNCTIONS: [&str; 5] = ["COUNT", "SUM", "AVG", "MIN", "MAX"];
+
+#[tauri::command]
+async fn get_grouped_table_data(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+    group_by_columns: Vec<String>,
+    aggregates: Vec<AggregateSpec>,
+) -> Result<TableData, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let table_columns = get_column_names(&conn, &table)?;
+    if table_columns.is_empty() {
+        return Err(format!("Table '{}' does not exist", table));
+    }
+    if group_by_columns.is_empty() && aggregates.is_empty() {
+        return Err("Nothing to select, add a group column or an aggregate".into());
+    }
+
+    let mut select_parts = Vec::new();
+    let mut columns = Vec::new();
+    for col in &group_by_columns {
+        if !table_columns.contains(col) {
+            return Err(format!("Column '{}' does not exist in '{}'", col, table));
+        }
+        select_parts.push(quote_identifier(col));
+        columns.push(col.clone());
+    }
+
+    for agg in &aggregates {
+        let function = agg.function.to_uppercase();
+        if !AGGREGATE_FUNCTIONS.contains(&function.as_str()) {
+            return Err(format!(
+                "Unsupported aggregate '{}', expected one of {}",
+                agg.function,
+                AGGREGATE_FUNCTIONS.join(", ")
+            ));
+        }
+        // COUNT(*) is the only aggregate that doesn't take a real column
+        let argument = if agg.column == "*" && function == "COUNT" {
+            "*".to_string()
+        } else if table_columns.contains(&agg.column) {
+            quote_identifier(&agg.column)
+        } else {
+            return Err(format!(
+                "Column '{}' does not exist in '{}'",
+                agg.column, table
+            ));
+        };
+        select_parts.push(format!(
+            "{}({}) AS {}",
+            function,
+            argument,
+            quote_identifier(&agg.alias)
+        ));
+        columns.push(agg.alias.clone());
+    }
+
+    let mut query = format!(
+        "SELECT {} FROM {}",
+        select_parts.join(", "),
+        quote_identifier(&table)
+    );
+    if !group_by_columns.is_empty() {
+        let group_list = group_by_columns
+            .iter()
+            .map(|c| quote_identifier(c))
+            .collect::<Vec<_>>()
+            .join(", ");
+        query.push_str(&format!(" GROUP BY {0} ORDER BY {0}", group_list));
+    }
+    query.push_str(" LIMIT 10000");
+
+    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let col_count = stmt.column_count();
+    let rows = stmt
+        .query_map([], |row| row_to_json_values(row, col_count))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(TableData {
+        columns,
+        rows,
+        total_pages: 1,
+        clamped: false,
+        warnings: Vec::new(),
+        has_more: false,
+        row_types: None,
+        change_token: None,
+        data_changed: false,
+        is_fts: false,
+        snippets: None,
+        next_cursor: None,
+        prev_cursor: None,
+    })
+}
+
+// Used when the linked SQLite predates PRAGMA function_list (3.30.0)
+const FALLBACK_FUNCTIONS: [(&str, &str, i32); 24] = [
+    ("abs", "s", 1),
+    ("coalesce", "s", -1),
+    ("date", "s", -1),
+    ("datetime", "s", -1),
+    ("glob", "s", 2),
+    ("hex", "s", 1),
+    ("ifnull", "s", 2),
+    ("instr", "s", 2),
+    ("julianday", "s", -1),
+    ("length", "s", 1),
+    ("like", "s", 2),
+    ("lower", "s", 1),
+    ("nullif", "s", 2),
+    ("replace", "s", 3),
+    ("round", "s", -1),
+    ("substr", "s", -1),
+    ("trim", "s", -1),
+    ("typeof", "s", 1),
+    ("upper", "s", 1),
+    ("avg", "a", 1),
+    ("count", "a", -1),
+    ("group_concat", "a", -1),
+    ("max", "a", -1),
+    ("sum", "a", 1),
+];
+
+const PIVOT_MAX_HEADERS: usize = 50;
+
+#[tauri::command]
+async fn get_pivot_data(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+    row_field: String,
+    col_field: String,
+    value_field: String,
+    aggregate: String,
+) -> Result<PivotResult, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let table_columns = get_column_names(&conn, &table)?;
+    if table_columns.is_empty() {
+        return Err(format!("Table '{}' does not exist", table));
+    }
+    for col in [&row_field, &col_field, &value_field] {
+        if !table_columns.contains(col) {
+            return Err(format!("Column '{}' does not exist in '{}'", col, table));
+        }
+    }
+    let function = aggregate.to_uppercase();
+    if !AGGREGATE_FUNCTIONS.contains(&function.as_str()) {
+        return Err(format!(
+            "Unsupported aggregate '{}', expected one of {}",
+            aggregate,
+            AGGREGATE_FUNCTIONS.join(", ")
+        ));
+    }
+
+    // One extra value tells whether the headers were cut off
+    let distinct_values = |column: &str| -> Result<Vec<rusqlite::types::Value>, String> {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT DISTINCT {0} FROM {1} ORDER BY {0} LIMIT {2}",
+                quote_identifier(column),
+                quote_identifier(&table),
+                PIVOT_MAX_HEADERS + 1
+            ))
+            .map_err(|e| e.to_string())?;
+        let values = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        Ok(values)
+    };
+    let mut row_values = distinct_values(&row_field)?;
+    let mut col_values = distinct_values(&col_field)?;
+    let truncated = row_values.len() > PIVOT_MAX_HEADERS || col_values.len() > PIVOT_MAX_HEADERS;
+    row_values.truncate(PIVOT_MAX_HEADERS);
+    col_values.truncate(PIVOT_MAX_HEADERS);
+
+    // IS instead of = so NULL is a header like any other value
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {}({}) FROM {} WHERE {} IS ?1 AND {} IS ?2",
+            function,
+            quote_identifier(&value_field),
+            quote_identifier(&table),
+            quote_identifier(&row_field),
+            quote_identifier(&col_field)
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut cells = Vec::with_capacity(row_values.len());
+    for row_value in &row_values {
+        let mut row_cells = Vec::with_capacity(col_values.len());
+        for col_value in &col_values {
+            let value: rusqlite::types::Value = stmt
+                .query_row(params![row_value, col_value], |row| row.get(0))
+                .map_err(|e| e.to_string())?;
+            // MIN/MAX over text columns give text, which has no place in a numeric cell
+            row_cells.push(match value {
+                rusqlite::types::Value::Integer(i) => Some(i as f64),
+                rusqlite::types::Value::Real(f) => Some(f),
+                _ => None,
+            });
+        }
+        cells.push(row_cells);
+    }
+
+    Ok(PivotResult {
+        row_headers: row_values.into_iter().map(sqlite_value_to_json).collect(),
+        col_headers: col_values.into_iter().map(sqlite_value_to_json).collect(),
+        cells,
+        truncated,
+    })
+}
+
+#[tauri::command]
+async fn get_function_list(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<Vec<FunctionInfo>, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+
+    // SQLite builds without pragma_function_list fail to prepare the query
+    match read_function_list(&conn) {
+        Ok(functions) => Ok(functions),
+        Err(_) => Ok(FALLBACK_FUNCTIONS
+            .iter()
+            .map(|(name, type_, narg)| FunctionInfo {
+                name: name.to_string(),
+                builtin: true,
+                type_: type_.to_string(),
+                enc: "utf8".to_string(),
+                narg: *narg,
+            })
+            .collect()),
+    }
+}
+
+fn read_function_list(conn: &Connection) -> rusqlite::Result<Vec<FunctionInfo>> {
+    let mut stmt = conn
+        .prepare("SELECT name, builtin, type, enc, narg FROM pragma_function_list ORDER BY name")?;
+    let functions = stmt
+        .query_map([], |row| {
+            Ok(FunctionInfo {
+                name: row.get(0)?,
+                builtin: row.get(1)?,
+                type_: row.get(2)?,
+                enc: row.get(3)?,
+                narg: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(functions)
+}
+
+fn sql_literal(value: &rusqlite::types::Value) -> String {
+    use rusqlite::types::Value;
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Integer(i) => i.to_string(),
+        // SQLite has no literal for infinity but parses out-of-range numbers as
+        // one; NaN is stored as NULL anyway
+        Value::Real(f) if f.is_nan() => "NULL".to_string(),
+        Value::Real(f) if *f == f64::INFINITY => "9e999".to_string(),
+        Value::Real(f) if *f == f64::NEG_INFINITY => "-9e999".to_string(),
+        // Debug keeps the decimal point so the value round-trips as REAL
+        Value::Real(f) => format!("{:?}", f),
+        Value::Text(s) => format!("'{}'", s.replace('\'', "''")),
+        Value::Blob(b) => format!(
+            "X'{}'",
+            b.iter()
+                .map(|byte| format!("{:02X}", byte))
+                .collect::<String>()
+        ),
+    }
+}
+
+#[tauri::command]
+async fn generate_insert_statements(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+    row_ids: Vec<serde_json::Value>,
+    pk_column: String,
+) -> Result<String, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let columns = get_column_names(&conn, &table)?;
+    if columns.is_empty() {
+        return Err(format!("Table '{}' does not exist", table));
+    }
+    if !columns.contains(&pk_column) {
+        return Err(format!(
+            "Column '{}' does not exist in '{}'",
+            pk_column, table
+        ));
+    }
+
+    let column_list = columns
+        .iter()
+        .map(|c| quote_identifier(c))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM {} WHERE {} = ?1",
+            column_list,
+            quote_identifier(&table),
+            quote_identifier(&pk_column)
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let mut statements = Vec::new();
+    for row_id in &row_ids {
+        let values: Vec<rusqlite::types::Value> = stmt
+            .query_row([json_to_sqlite_value(row_id)], |row| {
+                (0..columns.len()).map(|i| row.get(i)).collect()
+            })
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("No row in '{}' where {} = {}", table, pk_column, row_id))?;
+
+        statements.push(format!(
+            "INSERT INTO {} ({}) VALUES ({});",
+            quote_identifier(&table),
+            column_list,
+            values
+                .iter()
+                .map(sql_literal)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    Ok(statements.join("\n"))
+}
+
+// Schema-only dump, e.g. for keeping the schema under version control
+#[tauri::command]
+async fn get_full_schema_ddl(
+    state: State<'_, AppState>,
+    path: String,
+    include_system: bool,
+) -> Result<String, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let filter = if include_system {
+        ""
+    } else {
+        " AND name NOT LIKE 'sqlite_%'"
+    };
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT sql FROM sqlite_master WHERE sql IS NOT NULL{} ORDER BY type, name",
+            filter
+        ))
+        .map_err(|e| e.to_string())?;
+    let statements = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut ddl = format!(
+        "-- Generated by DB Visualizer on {}\n\n",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+    );
+    if !statements.is_empty() {
+        ddl.push_str(&statements.join(";\n\n"));
+        ddl.push_str(";\n");
+    }
+    Ok(ddl)
+}
+
+// Columns in tables smaller than this are too sparse for index advice to matter
+const INDEX_HINT_MIN_ROWS: i64 = 1000;
+
+fn migration_hint(severity: &str, category: &str, message: String) -> MigrationHint {
+    MigrationHint {
+        severity: severity.to_string(),
+        category: category.to_string(),
+        message,
+    }
+}
+
+#[tauri::command]
+async fn get_migration_hints(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<Vec<MigrationHint>, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let mut stmt = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' AND sql NOT LIKE 'CREATE VIRTUAL TABLE%'")
+        .map_err(|e| e.to_string())?;
+    let tables: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut hints = Vec::new();
+    for table in &tables {
+        let columns = get_table_columns(&conn, table)?;
+        if columns.is_empty() {
+            continue;
+        }
+
+        if columns.iter().all(|c| c.primary_key == 0) {
+            hints.push(migration_hint(
+                "warning",
+                "normalization",
+                format!("Table '{}' has no primary key", table),
+            ));
+        }
+
+        // Only the leading column of an index can serve lookups on its own
+        let mut stmt = conn
+            .prepare("SELECT ii.name FROM pragma_index_list(?1) il, pragma_index_info(il.name) ii WHERE ii.seqno = 0")
+            .map_err(|e| e.to_string())?;
+        let indexed: Vec<String> = stmt
+            .query_map(params![table], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        // One scan per table: distinct count, null count and text count for each column
+        let mut select_parts = vec!["COUNT(*)".to_string()];
+        for col in &columns {
+            let q = quote_identifier(&col.name);
+            select_parts.push(format!(
+                "COUNT(DISTINCT {0}), TOTAL({0} IS NULL), TOTAL(typeof({0}) = 'text')",
+                q
+            ));
+        }
+        let stats: Vec<i64> = conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM {}",
+                    select_parts.join(", "),
+                    quote_identifier(table)
+                ),
+                [],
+                |row| {
+                    (0..=columns.len() * 3)
+                        .map(|i| row.get::<_, f64>(i).map(|v| v as i64))
+                        .collect()
+                },
+            )
+            .map_err(|e| e.to_string())?;
+        let row_count = stats[0];
+        if row_count == 0 {
+            continue;
+        }
+
+        for (i, col) in columns.iter().enumerate() {
+            let distinct = stats[1 + i * 3];
+            let nulls = stats[2 + i * 3];
+            let texts = stats[3 + i * 3];
+
+            if col.primary_key == 0
+                && row_count >= INDEX_HINT_MIN_ROWS
+                && distinct * 10 >= row_count * 9
+                && !indexed.contains(&col.name)
+            {
+                hints.push(migration_hint(
+                    "info",
+                    "index",
+                    format!(
+                        "Column '{}.{}' is nearly unique ({} distinct of {} rows) but has no index",
+                        table, col.name, distinct, row_count
+                    ),
+                ));
+            }
+
+            if column_affinity(&col.declared_type) == "INTEGER" && texts > 0 {
+                hints.push(migration_hint(
+                    "warning",
+                    "type",
+                    format!(
+                        "Column '{}.{}' is declared as {} but holds {} TEXT values",
+                        table, col.name, col.declared_type, texts
+                    ),
+                ));
+            }
+
+            if !col.not_null && col.primary_key == 0 && nulls == 0 {
+                hints.push(migration_hint(
+                    "info",
+                    "constraint",
+                    format!(
+                        "Column '{}.{}' has no NULL values and could be declared NOT NULL",
+                        table, col.name
+                    ),
+                ));
+            }
+        }
+    }
+
+    Ok(hints)
+}
+
+fn apply_pragmas(
+    conn: &Connection,
+    cache_size_kb: Option<i64>,
+    mmap_size_mb: Option<i64>,
+    busy_timeout_ms: Option<i64>,
+) -> Result<(), String> {
+    // A negative cache_size is interpreted by SQLite as KiB instead of pages
+    if let Some(kb) = cache_size_kb {
+        conn.execute_batch(&format!("PRAGMA cache_size = {}", -kb.saturating_abs()))
+            .map_err(|e| e.to_string())?;
+    }
+    if let Some(mb) = mmap_size_mb {
+        conn.execute_batch(&format!(
+            "PRAGMA mmap_size = {}",
+            mb.max(0).saturating_mul(1024 * 1024)
+        ))
+        .map_err(|e| e.to_string())?;
+    }
+    if let Some(ms) = busy_timeout_ms {
+        conn.execute_batch(&format!("PRAGMA busy_timeout = {}", ms.max(0)))
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn apply_connection_prefs(
+    conn: &Connection,
+    path: &str,
+    prefs_conn: &Connection,
+) -> Result<(), String> {
+    // Saved under the canonical path, rows saved before that under the path as given
+    let canonical = canonical_db_path(path).unwrap_or_else(|_| path.to_string());
+    let prefs = prefs_conn
+        .query_row(
+            "SELECT cache_size_kb, mmap_size_mb, busy_timeout_ms FROM connection_preferences WHERE db_path IN (?1, ?2) ORDER BY db_path = ?1 DESC LIMIT 1",
+            params![canonical, path],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    match prefs {
+        Some((cache_size_kb, mmap_size_mb, busy_timeout_ms)) => {
+            apply_pragmas(conn, cache_size_kb, mmap_size_mb, busy_timeout_ms)
+        }
+        None => Ok(()),
+    }
+}
+
+// Every connection to a user database goes through these, so the pragmas saved
+// with set_connection_pragmas apply to whichever command opens the file
+fn open_database_with_prefs(
+    prefs_conn: &Connection,
+    path: &str,
+    flags: OpenFlags,
+) -> Result<Connection, String> {
+    // Without CREATE a failed open usually means the file can't be written
+    let conn = Connection::open_with_flags(path, flags).map_err(|e| {
+        if flags.contains(OpenFlags::SQLITE_OPEN_READ_WRITE)
+            && !flags.contains(OpenFlags::SQLITE_OPEN_CREATE)
+        {
+            map_write_error(e)
+        } else {
+            e.to_string()
+        }
+    })?;
+    apply_connection_prefs(&conn, path, prefs_conn)?;
+    Ok(conn)
+}
+
+fn open_database_with_flags(
+    state: &AppState,
+    path: &str,
+    flags: OpenFlags,
+) -> Result<Connection, String> {
+    open_database_with_prefs(&get_metadata_conn(state)?, path, flags)
+}
+
+fn open_with_connection_prefs(state: &AppState, path: &str) -> Result<Connection, String> {
+    open_database_with_flags(state, path, OpenFlags::default())
+}
+
+// Large enough for any sensible setting (about 1 TB of cache or map), small
+// enough that the KiB and MiB conversions can't overflow
+const MAX_PRAGMA_VALUE: i64 = 1 << 30;
+
+#[tauri::command]
+async fn set_connection_pragmas(
+    state: State<'_, AppState>,
+    path: String,
+    cache_size_kb: Option<i64>,
+    mmap_size_mb: Option<i64>,
+    busy_timeout_ms: Option<i64>,
+) -> Result<(), String> {
+    for (name, value) in [
+        ("cache_size_kb", cache_size_kb),
+        ("mmap_size_mb", mmap_size_mb),
+        ("busy_timeout_ms", busy_timeout_ms),
+    ] {
+        if value.is_some_and(|v| !(0..=MAX_PRAGMA_VALUE).contains(&v)) {
+            return Err(format!(
+                "{} must be between 0 and {}",
+                name, MAX_PRAGMA_VALUE
+            ));
+        }
+    }
+
+    // Try the values on a throwaway connection before persisting them
+    let test_conn = open_with_connection_prefs(&state, &path)?;
+    apply_pragmas(&test_conn, cache_size_kb, mmap_size_mb, busy_timeout_ms)?;
+
+    // Keyed by the canonical path so every spelling of the file finds them
+    let key = canonical_db_path(&path)?;
+    let conn = get_metadata_conn(&state)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO connection_preferences (db_path, cache_size_kb, mmap_size_mb, busy_timeout_ms) VALUES (?1, ?2, ?3, ?4)",
+        params![key, cache_size_kb, mmap_size_mb, busy_timeout_ms],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// (pattern in upper-cased schema SQL, source dialect, suggestion)
+const DIALECT_PATTERNS: [(&str, &str, &str); 11] = [
+    (
+        "AUTO_INCREMENT",
+        "MySQL",
+        "use INTEGER PRIMARY KEY AUTOINCREMENT, AUTO_INCREMENT is ignored by SQLite",
+    ),
+    (
+        "TINYINT(1)",
+        "MySQL",
+        "declare booleans as INTEGER holding 0 or 1",
+    ),
+    (
+        "UNSIGNED",
+        "MySQL",
+        "SQLite has no unsigned integers, add a CHECK (col >= 0) constraint instead",
+    ),
+    (
+        "ENUM(",
+        "MySQL",
+        "replace ENUM with TEXT and a CHECK (col IN (...)) constraint",
+    ),
+    (
+        "ENGINE=",
+        "MySQL",
+        "table engine options have no effect in SQLite",
+    ),
+    (
+        "`",
+        "MySQL",
+        "quote identifiers with double quotes instead of backticks",
+    ),
+    (
+        "BIGSERIAL",
+        "PostgreSQL",
+        "use INTEGER PRIMARY KEY for an auto-assigned rowid alias",
+    ),
+    (
+        "SERIAL",
+        "PostgreSQL",
+        "use INTEGER PRIMARY KEY for an auto-assigned rowid alias",
+    ),
+    (
+        "::",
+        "PostgreSQL",
+        "replace :: casts with CAST(expr AS type)",
+    ),
+    (
+        "WITH TIME ZONE",
+        "PostgreSQL",
+        "SQLite has no time zone aware types, store ISO 8601 text with an offset",
+    ),
+    (
+        "NOW()",
+        "MySQL/PostgreSQL",
+        "use CURRENT_TIMESTAMP or datetime('now') instead of NOW()",
+    ),
+];
+
+#[tauri::command]
+async fn detect_database_dialect_hints(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<DialectReport, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let mut stmt = conn
+        .prepare("SELECT name, sql FROM sqlite_master WHERE sql IS NOT NULL AND name NOT LIKE 'sqlite_%'")
+        .map_err(|e| e.to_string())?;
+    let rows: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut report = DialectReport {
+        detected_patterns: Vec::new(),
+        compatibility_warnings: Vec::new(),
+    };
+    for (name, sql) in rows {
+        let upper = sql.to_ascii_uppercase();
+        for (pattern, dialect, suggestion) in DIALECT_PATTERNS {
+            // BIGSERIAL also contains SERIAL, report it only once
+            if pattern == "SERIAL" && upper.contains("BIGSERIAL") {
+                continue;
+            }
+            if upper.contains(pattern) {
+                report
+                    .detected_patterns
+                    .push(format!("{}: {} ({})", name, pattern, dialect));
+                report
+                    .compatibility_warnings
+                    .push(format!("{}: {}", name, suggestion));
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+#[tauri::command]
+async fn find_orphaned_rows(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+    fk_id: Option<i32>,
+) -> Result<Vec<OrphanedRow>, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    if !table_exists(&conn, &table)? {
+        return Err(format!("Table '{}' does not exist", table));
+    }
+
+    // rowid is NULL for WITHOUT ROWID tables, those violations can't be pointed at
+    let mut stmt = conn
+        .prepare("SELECT \"rowid\", parent, fkid FROM pragma_foreign_key_check(?1) WHERE \"rowid\" IS NOT NULL AND (?2 IS NULL OR fkid = ?2)")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![table, fk_id], |row| {
+            Ok(OrphanedRow {
+                rowid: row.get(0)?,
+                parent_table: row.get(1)?,
+                fk_id: row.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+#[tauri::command]
+async fn get_column_histogram(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+    column: String,
+    bucket_count: u32,
+) -> Result<Vec<HistogramBucket>, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    if !get_column_names(&conn, &table)?.contains(&column) {
+        return Err(format!("Column '{}' does not exist in '{}'", column, table));
+    }
+
+    // Text and blob values are left out of the buckets
+    let numeric = format!(
+        "CASE WHEN typeof({0}) IN ('integer', 'real') THEN {0} END",
+        quote_identifier(&column)
+    );
+    let (min, max, non_null): (Option<f64>, Option<f64>, i64) = conn
+        .query_row(
+            &format!(
+                "SELECT MIN({0}), MAX({0}), COUNT({1}) FROM {2}",
+                numeric,
+                quote_identifier(&column),
+                quote_identifier(&table)
+            ),
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| e.to_string())?;
+    if non_null == 0 {
+        return Err(format!("Column '{}' only contains NULL values", column));
+    }
+    let (Some(min), Some(max)) = (min, max) else {
+        return Err(format!("Column '{}' is not numeric", column));
+    };
+
+    let bucket_count = if min == max {
+        1
+    } else {
+        bucket_count.clamp(1, 100)
+    };
+    let width = (max - min) / bucket_count as f64;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT COUNT(*) FROM {0} WHERE {1} >= ?1 AND ({1} < ?2 OR ?3)",
+            quote_identifier(&table),
+            numeric
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut buckets = Vec::with_capacity(bucket_count as usize);
+    for i in 0..bucket_count {
+        let low = min + width * i as f64;
+        let last = i == bucket_count - 1;
+        let high = if last { max } else { low + width };
+        // Buckets are half-open except the last, which has to include max itself
+        let count: i64 = stmt
+            .query_row(params![low, high, last], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        buckets.push(HistogramBucket {
+            low,
+            high,
+            count: count as u64,
+        });
+    }
+
+    Ok(buckets)
+}
+
+const LOREM_WORDS: [&str; 16] = [
+    "lorem",
+    "ipsum",
+    "dolor",
+    "sit",
+    "amet",
+    "consectetur",
+    "adipiscing",
+    "elit",
+    "sed",
+    "do",
+    "eiusmod",
+    "tempor",
+    "incididunt",
+    "ut",
+    "labore",
+    "magna",
+];
+
+// SplitMix64, good enough for filler data and reproducible from a seed
+struct TestDataRng(u64);
+
+impl TestDataRng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    fn value_for(&mut self, column: &ColumnInfo) -> rusqlite::types::Value {
+        use rusqlite::types::Value;
+        if !column.not_null && self.below(10) == 0 {
+            return Value::Null;
+        }
+        match column_affinity(&column.declared_type) {
+            "REAL" => Value::Real((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64 * 1000.0),
+            "TEXT" => Value::Text(
+                (0..1 + self.below(5))
+                    .map(|_| LOREM_WORDS[self.below(LOREM_WORDS.len() as u64) as usize])
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            "BLOB" => Value::Blob((0..16).map(|_| self.next_u64() as u8).collect()),
+            _ => Value::Integer(self.below(1_000_000) as i64),
+        }
+    }
+}
+
+// Checked in this order, so 8-digit integers count as YYYYMMDD before Unix seconds
+const DATE_FORMATS: [&str; 3] = ["ISO 8601", "YYYYMMDD", "Unix timestamp"];
+
+fn parse_date_value(value: &rusqlite::types::Value, format: &str) -> Option<chrono::NaiveDateTime> {
+    use rusqlite::types::Value;
+    let parse_yyyymmdd = |s: &str| {
+        chrono::NaiveDate::parse_from_str(s, "%Y%m%d")
+            .ok()
+            .filter(|_| s.len() == 8)
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+    };
+    match (format, value) {
+        ("ISO 8601", Value::Text(s)) => parse_iso8601(s),
+        ("YYYYMMDD", Value::Integer(i)) => parse_yyyymmdd(&i.to_string()),
+        ("YYYYMMDD", Value::Text(s)) => parse_yyyymmdd(s),
+        ("Unix timestamp", Value::Integer(i)) => {
+            chrono::DateTime::from_timestamp(*i, 0).map(|d| d.naive_utc())
+        }
+        ("Unix timestamp", Value::Real(f)) => {
+            chrono::DateTime::from_timestamp_millis((f * 1000.0) as i64).map(|d| d.naive_utc())
+        }
+        _ => None,
+    }
+}
+
+#[tauri::command]
+async fn detect_date_range(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+    column: String,
+) -> Result<DateRangeInfo, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    if !get_column_names(&conn, &table)?.contains(&column) {
+        return Err(format!("Column '{}' does not exist in '{}'", column, table));
+    }
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {0} FROM {1} WHERE {0} IS NOT NULL LIMIT 100",
+            quote_identifier(&column),
+            quote_identifier(&table)
+        ))
+        .map_err(|e| e.to_string())?;
+    let samples: Vec<rusqlite::types::Value> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    // The format most of the sample parses with; ties go to the earlier format
+    let mut best: Option<(&str, usize)> = None;
+    for format in DATE_FORMATS {
+        let parsed = samples
+            .iter()
+            .filter(|v| parse_date_value(v, format).is_some())
+            .count();
+        if parsed > 0 && best.is_none_or(|(_, count)| parsed > count) {
+            best = Some((format, parsed));
+        }
+    }
+    let detected_format = best.map(|(format, _)| format).unwrap_or("Unknown");
+
+    let (min, max, null_count): (rusqlite::types::Value, rusqlite::types::Value, u64) = conn
+        .query_row(
+            &format!(
+                "SELECT MIN({0}), MAX({0}), COUNT(*) - COUNT({0}) FROM {1}",
+                quote_identifier(&column),
+                quote_identifier(&table)
+            ),
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| e.to_string())?;
+
+    // Dates that parse are normalized, anything else is passed through as stored
+    let parsed_min = parse_date_value(&min, detected_format);
+    let parsed_max = parse_date_value(&max, detected_format);
+    let display = |value: rusqlite::types::Value, parsed: Option<chrono::NaiveDateTime>| {
+        parsed
+            .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string())
+            .or(match value {
+                rusqlite::types::Value::Null => None,
+                rusqlite::types::Value::Integer(i) => Some(i.to_string()),
+                rusqlite::types::Value::Real(f) => Some(f.to_string()),
+                rusqlite::types::Value::Text(s) => Some(s),
+                rusqlite::types::Value::Blob(b) => Some(format!("<{} bytes>", b.len())),
+            })
+    };
+    let span_days = match (parsed_min, parsed_max) {
+        (Some(min), Some(max)) => Some((max - min).num_seconds() as f64 / 86_400.0),
+        _ => None,
+    };
+
+    Ok(DateRangeInfo {
+        min_date: display(min, parsed_min),
+        max_date: display(max, parsed_max),
+        span_days,
+        null_count,
+        detected_format: detected_format.to_string(),
+    })
+}
+
+#[tauri::command]
+async fn compute_column_correlation(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+    col_a: String,
+    col_b: String,
+) -> Result<CorrelationResult, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let table_columns = get_column_names(&conn, &table)?;
+    for col in [&col_a, &col_b] {
+        if !table_columns.contains(col) {
+            return Err(format!("Column '{}' does not exist in '{}'", col, table));
+        }
+    }
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {0}, {1} FROM {2} WHERE {0} IS NOT NULL AND {1} IS NOT NULL",
+            quote_identifier(&col_a),
+            quote_identifier(&col_b),
+            quote_identifier(&table)
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+
+    // Welford's running means and co-moments, so no values are kept in memory
+    let (mut n, mut mean_a, mut mean_b) = (0u64, 0.0f64, 0.0f64);
+    let (mut m2_a, mut m2_b, mut co_moment) = (0.0f64, 0.0f64, 0.0f64);
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let as_number = |i: usize| match row.get::<_, rusqlite::types::Value>(i) {
+            Ok(rusqlite::types::Value::Integer(v)) => Some(v as f64),
+            Ok(rusqlite::types::Value::Real(v)) => Some(v),
+            _ => None,
+        };
+        // Pairs with a text or blob side can't take part
+        let (Some(a), Some(b)) = (as_number(0), as_number(1)) else {
+            continue;
+        };
+
+        n += 1;
+        let delta_a = a - mean_a;
+        mean_a += delta_a / n as f64;
+        let delta_b = b - mean_b;
+        mean_b += delta_b / n as f64;
+        m2_a += delta_a * (a - mean_a);
+        m2_b += delta_b * (b - mean_b);
+        co_moment += delta_a * (b - mean_b);
+    }
+
+    // Sample standard deviations; r is undefined without two pairs or with a constant column
+    let std = |m2: f64| {
+        if n > 1 {
+            (m2 / (n - 1) as f64).sqrt()
+        } else {
+            0.0
+        }
+    };
+    let pearson_r = if n >= 2 && m2_a > 0.0 && m2_b > 0.0 {
+        Some(co_moment / (m2_a.sqrt() * m2_b.sqrt()))
+    } else {
+        None
+    };
+
+    Ok(CorrelationResult {
+        pearson_r,
+        n_pairs: n,
+        col_a_mean: mean_a,
+        col_b_mean: mean_b,
+        col_a_std: std(m2_a),
+        col_b_std: std(m2_b),
+    })
+}
+
+const ENTROPY_MAX_DISTINCT: usize = 100_000;
+
+#[tauri::command]
+async fn compute_column_entropy(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+    column: String,
+) -> Result<EntropyResult, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    if !get_column_names(&conn, &table)?.contains(&column) {
+        return Err(format!("Column '{}' does not exist in '{}'", column, table));
+    }
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {0} FROM {1} WHERE {0} IS NOT NULL",
+            quote_identifier(&column),
+            quote_identifier(&table)
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+
+    // Keyed by storage class and raw bytes, so 1 and '1' count as different values
+    let mut frequencies: HashMap<(u8, Vec<u8>), u64> = HashMap::new();
+    let mut total_count = 0u64;
+    let mut warnings = Vec::new();
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let key = match row.get_ref(0).map_err(|e| e.to_string())? {
+            rusqlite::types::ValueRef::Null => continue,
+            rusqlite::types::ValueRef::Integer(i) => (1, i.to_le_bytes().to_vec()),
+            rusqlite::types::ValueRef::Real(f) => (2, f.to_bits().to_le_bytes().to_vec()),
+            rusqlite::types::ValueRef::Text(t) => (3, t.to_vec()),
+            rusqlite::types::ValueRef::Blob(b) => (4, b.to_vec()),
+        };
+        if frequencies.len() >= ENTROPY_MAX_DISTINCT && !frequencies.contains_key(&key) {
+            warnings.push(format!(
+                "Stopped at {} distinct values, the entropy only covers the first {} values",
+                ENTROPY_MAX_DISTINCT, total_count
+            ));
+            break;
+        }
+        *frequencies.entry(key).or_insert(0) += 1;
+        total_count += 1;
+    }
+
+    let shannon_entropy = frequencies
+        .values()
+        .map(|&count| {
+            let p = count as f64 / total_count as f64;
+            -p * p.log2()
+        })
+        .sum::<f64>();
+    let distinct_count = frequencies.len() as u64;
+    // Reached when every distinct value is equally common
+    let max_possible_entropy = if distinct_count > 1 {
+        (distinct_count as f64).log2()
+    } else {
+        0.0
+    };
+
+    Ok(EntropyResult {
+        shannon_entropy,
+        distinct_count,
+        total_count,
+        max_possible_entropy,
+        warnings,
+    })
+}
+
+#[tauri::command]
+async fn generate_test_data(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+    row_count: u32,
+    seed: Option<u64>,
+) -> Result<u64, String> {
+    let mut conn = open_read_write(&state, &path)?;
+    let table_columns = get_table_columns(&conn, &table)?;
+    if table_columns.is_empty() {
+        return Err(format!("Table '{}' does not exist", table));
+    }
+
+    // Leave rowid aliases and columns with defaults to SQLite
+    let is_rowid_alias = |c: &ColumnInfo| {
+        c.primary_key == 1
+            && c.declared_type.eq_ignore_ascii_case("INTEGER")
+            && table_columns.iter().filter(|o| o.primary_key > 0).count() == 1
+    };
+    let columns: Vec<&ColumnInfo> = table_columns
+        .iter()
+        .filter(|c| !is_rowid_alias(c) && c.default_value.is_none())
+        .collect();
+
+    let insert_sql = if columns.is_empty() {
+        format!("INSERT INTO {} DEFAULT VALUES", quote_identifier(&table))
+    } else {
+        format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            quote_identifier(&table),
+            columns
+                .iter()
+                .map(|c| quote_identifier(&c.name))
+                .collect::<Vec<_>>()
+                .join(", "),
+            vec!["?"; columns.len()].join(", ")
+        )
+    };
+
+    let seed = seed.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default()
+    });
+    let mut rng = TestDataRng(seed);
+
+    let mut inserted = 0u64;
+    while inserted < row_count as u64 {
+        let batch = (row_count as u64 - inserted).min(500);
+        let tx = conn.transaction().map_err(map_write_error)?;
+        {
+            let mut stmt = tx.prepare(&insert_sql).map_err(|e| e.to_string())?;
+            for _ in 0..batch {
+                let values: Vec<rusqlite::types::Value> =
+                    columns.iter().map(|c| rng.value_for(c)).collect();
+                stmt.execute(params_from_iter(values))
+                    .map_err(map_write_error)?;
+            }
+        }
+        tx.commit().map_err(map_write_error)?;
+        inserted += batch;
+    }
+
+    Ok(inserted)
+}
+
+// None means the value can't be represented in the target type without losing data
+fn coerce_value(
+    value: rusqlite::types::Value,
+    target_type: &str,
+) -> Option<rusqlite::types::Value> {
+    use rusqlite::types::Value;
+    match (target_type, value) {
+        (_, Value::Null) => Some(Value::Null),
+        ("INTEGER", Value::Integer(i)) => Some(Value::Integer(i)),
+        // `as` saturates out-of-range floats, so only in-range whole numbers convert
+        ("INTEGER", Value::Real(f))
+            if f.fract() == 0.0 && f >= i64::MIN as f64 && f < i64::MAX as f64 =>
+        {
+            Some(Value::Integer(f as i64))
+        }
+        ("INTEGER", Value::Text(s)) => s.trim().parse().ok().map(Value::Integer),
+        // Integers beyond 2^53 don't survive the round trip through f64, and
+        // i64::MAX rounds up to 2^63 which `as` saturates back to itself
+        ("REAL", Value::Integer(i)) if (i as f64) < i64::MAX as f64 && (i as f64) as i64 == i => {
+            Some(Value::Real(i as f64))
+        }
+        ("REAL", Value::Real(f)) => Some(Value::Real(f)),
+        ("REAL", Value::Text(s)) => s.trim().parse().ok().map(Value::Real),
+        ("TEXT", Value::Integer(i)) => Some(Value::Text(i.to_string())),
+        ("TEXT", Value::Real(f)) => Some(Value::Text(f.to_string())),
+        ("TEXT", Value::Text(s)) => Some(Value::Text(s)),
+        ("TEXT", Value::Blob(b)) => String::from_utf8(b).ok().map(Value::Text),
+        ("BLOB", Value::Integer(i)) => Some(Value::Blob(i.to_string().into_bytes())),
+        ("BLOB", Value::Real(f)) => Some(Value::Blob(f.to_string().into_bytes())),
+        ("BLOB", Value::Text(s)) => Some(Value::Blob(s.into_bytes())),
+        ("BLOB", Value::Blob(b)) => Some(Value::Blob(b)),
+        _ => None,
+    }
+}
+
+#[tauri::command]
+async fn convert_column_type(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+    column: String,
+    target_type: String,
+) -> Result<ConversionReport, String> {
+    let target_type = target_type.to_uppercase();
+    if !["INTEGER", "REAL", "TEXT", "BLOB"].contains(&target_type.as_str()) {
+        return Err(format!(
+            "Unsupported target type '{}', expected INTEGER, REAL, TEXT or BLOB",
+            target_type
+        ));
+    }
+
+    let mut conn = open_read_write(&state, &path)?;
+    let columns = get_column_names(&conn, &table)?;
+    if !columns.contains(&column) {
+        return Err(format!("Column '{}' does not exist in '{}'", column, table));
+    }
+
+    // The column is rebuilt with a bare ADD COLUMN, so anything declared on it
+    // would be silently dropped
+    let table_sql: String = conn
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            params![table],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let constraints = column_constraint_kinds(&table_sql, &column);
+    if !constraints.is_empty() {
+        return Err(format!(
+            "Column '{}' has constraints that the conversion would drop ({})",
+            column,
+            constraints.join(", ")
+        ));
+    }
+
+    let temp_column = format!("{}__converted", column);
+    if columns.contains(&temp_column) {
+        return Err(format!(
+            "Column '{}' already exists in '{}'",
+            temp_column, table
+        ));
+    }
+
+    let table_q = quote_identifier(&table);
+    let column_q = quote_identifier(&column);
+    let temp_q = quote_identifier(&temp_column);
+    let mut report = ConversionReport {
+        rows_converted: 0,
+        rows_with_null_after_coerce: 0,
+        rows_failed: 0,
+    };
+
+    // DDL is transactional in SQLite, a failed DROP COLUMN (e.g. the column is
+    // indexed or part of a key) rolls the whole conversion back
+    let tx = conn.transaction().map_err(map_write_error)?;
+    tx.execute(
+        &format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            table_q, temp_q, target_type
+        ),
+        [],
+    )
+    .map_err(map_write_error)?;
+    {
+        let mut select = tx
+            .prepare(&format!("SELECT rowid, {} FROM {}", column_q, table_q))
+            .map_err(|e| e.to_string())?;
+        let rows: Vec<(i64, rusqlite::types::Value)> = select
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        let mut update = tx
+            .prepare(&format!(
+                "UPDATE {} SET {} = ?1 WHERE rowid = ?2",
+                table_q, temp_q
+            ))
+            .map_err(|e| e.to_string())?;
+        for (rowid, value) in rows {
+            let was_null = value == rusqlite::types::Value::Null;
+            let converted = match coerce_value(value, &target_type) {
+                Some(v) => {
+                    if !was_null {
+                        report.rows_converted += 1;
+                    }
+                    v
+                }
+                None => {
+                    report.rows_failed += 1;
+                    rusqlite::types::Value::Null
+                }
+            };
+            if converted == rusqlite::types::Value::Null {
+                report.rows_with_null_after_coerce += 1;
+            }
+            update
+                .execute(params![converted, rowid])
+                .map_err(map_write_error)?;
+        }
+    }
+
+    // Returning early drops the transaction, which rolls it back
+    if report.rows_failed > 0 {
+        return Err(format!(
+            "{} values can't be converted to {} and would become NULL, nothing was changed",
+            report.rows_failed, target_type
+        ));
+    }
+
+    // The converted column ends up last in the column order
+    tx.execute(
+        &format!("ALTER TABLE {} DROP COLUMN {}", table_q, column_q),
+        [],
+    )
+    .map_err(map_write_error)?;
+    tx.execute(
+        &format!(
+            "ALTER TABLE {} RENAME COLUMN {} TO {}",
+            table_q, temp_q, column_q
+        ),
+        [],
+    )
+    .map_err(map_write_error)?;
+    tx.commit().map_err(map_write_error)?;
+
+    Ok(report)
+}
+
+#[tauri::command]
+async fn get_expression_indexes(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<Vec<ExpressionIndexInfo>, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    // index_xinfo reports expression terms with cid -2, auto indexes have no sql
+    let mut stmt = conn
+        .prepare("SELECT m.name, m.tbl_name, m.sql FROM sqlite_master m WHERE m.type = 'index' AND m.sql IS NOT NULL AND EXISTS (SELECT 1 FROM pragma_index_xinfo(m.name) WHERE cid = -2) ORDER BY m.tbl_name, m.name")
+        .map_err(|e| e.to_string())?;
+    let rows: Vec<(String, String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(index_name, table_name, sql)| ExpressionIndexInfo {
+            index_name,
+            table_name,
+            expression: parse_index_terms(&sql),
+        })
+        .collect())
+}
+
+// Extracts the parenthesized term list following `ON table` in CREATE INDEX
+fn parse_index_terms(sql: &str) -> String {
+    let upper = sql.to_ascii_uppercase();
+    let Some(on_pos) = upper.find(" ON ") else {
+        return String::new();
+    };
+    let rest = &sql[on_pos + " ON ".len()..];
+    let Some(open) = rest.find('(') else {
+        return String::new();
+    };
+
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    for (i, c) in rest[open..].char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"' | '`') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => {
+                depth -= 1;
+                if depth == 0 {
+                    return rest[open + 1..open + i].trim().to_string();
+                }
+            }
+            _ => {}
+        }
+    }
+    rest[open + 1..].trim().to_string()
+}
+
+// Copies one attached `src` table into main, returning (inserted, skipped) rows
+fn merge_table(
+    tx: &Connection,
+    table: &str,
+    sql: &str,
+    insert_verb: &str,
+) -> Result<(u64, u64), String> {
+    // The unqualified CREATE TABLE from the source lands in main
+    if !table_exists(tx, table)? {
+        tx.execute(sql, []).map_err(|e| e.to_string())?;
+    }
+
+    // Only copy columns both sides have, the layouts may have drifted apart
+    let target_columns = get_column_names(tx, table)?;
+    let mut src_stmt = tx
+        .prepare(&format!(
+            "PRAGMA src.table_info({})",
+            quote_identifier(table)
+        ))
+        .map_err(|e| e.to_string())?;
+    let column_list = src_stmt
+        .query_map([], |row| row.get::<_, String>(1))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|c| target_columns.contains(c))
+        .map(|c| quote_identifier(&c))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let source_rows: u64 = tx
+        .query_row(
+            &format!("SELECT COUNT(*) FROM src.{}", quote_identifier(table)),
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let inserted = tx
+        .execute(
+            &format!(
+                "{0} INTO main.{1} ({2}) SELECT {2} FROM src.{1}",
+                insert_verb,
+                quote_identifier(table),
+                column_list
+            ),
+            [],
+        )
+        .map_err(|e| e.to_string())? as u64;
+    Ok((inserted, source_rows.saturating_sub(inserted)))
+}
+
+#[tauri::command]
+async fn merge_databases(
+    state: State<'_, AppState>,
+    target_path: String,
+    source_path: String,
+    conflict_strategy: String,
+) -> Result<MergeResult, String> {
+    let insert_verb = match conflict_strategy.as_str() {
+        "skip" => "INSERT OR IGNORE",
+        "replace" => "INSERT OR REPLACE",
+        "abort" => "INSERT",
+        other => {
+            return Err(format!(
+                "Unsupported conflict strategy '{}', expected skip, replace or abort",
+                other
+            ))
+        }
+    };
+
+    let mut conn = open_read_write(&state, &target_path)?;
+    conn.execute("ATTACH DATABASE ?1 AS src", params![source_path])
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare("SELECT name, sql FROM src.sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' AND sql NOT LIKE 'CREATE VIRTUAL TABLE%'")
+        .map_err(|e| e.to_string())?;
+    let tables: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    drop(stmt);
+
+    let mut result = MergeResult {
+        tables_merged: 0,
+        rows_inserted: 0,
+        rows_skipped: 0,
+        errors: Vec::new(),
+    };
+
+    // With "abort" any failure rolls back the whole merge, otherwise the failing
+    // table is reported and the rest are still merged
+    let tx = conn.transaction().map_err(map_write_error)?;
+    for (table, sql) in &tables {
+        let merged = merge_table(&tx, table, sql, insert_verb);
+
+        match merged {
+            Ok((inserted, skipped)) => {
+                result.tables_merged += 1;
+                result.rows_inserted += inserted;
+                result.rows_skipped += skipped;
+            }
+            Err(e) if conflict_strategy == "abort" => {
+                return Err(format!("Merging '{}' failed: {}", table, e));
+            }
+            Err(e) => result.errors.push(format!("{}: {}", table, e)),
+        }
+    }
+    tx.commit().map_err(map_write_error)?;
+
+    conn.execute("DETACH DATABASE src", [])
+        .map_err(|e| e.to_string())?;
+    Ok(result)
+}
+
+#[tauri::command]
+async fn describe_table(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+) -> Result<String, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let columns = get_table_columns(&conn, &table)?;
+    if columns.is_empty() {
+        return Err(format!("Table '{}' does not exist", table));
+    }
+
+    // Pipes would otherwise split a cell in the Markdown table
+    let cell = |s: &str| s.replace('|', "\\|");
+    let mut out = format!("### {}\n\n", cell(&table));
+    out.push_str("| Column | Type | Nullable | Default | Key |\n");
+    out.push_str("| --- | --- | --- | --- | --- |\n");
+    for col in &columns {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            cell(&col.name),
+            cell(&col.declared_type),
+            if col.not_null { "NO" } else { "YES" },
+            cell(col.default_value.as_deref().unwrap_or("")),
+            if col.primary_key > 0 { "PK" } else { "" }
+        ));
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT il.name, il.\"unique\", (SELECT group_concat(coalesce(ii.name, '<expression>'), ', ') FROM (SELECT name FROM pragma_index_xinfo(il.name) WHERE key ORDER BY seqno) ii) FROM pragma_index_list(?1) il ORDER BY il.name")
+        .map_err(|e| e.to_string())?;
+    let indexes: Vec<(String, bool, String)> = stmt
+        .query_map(params![table], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    out.push_str("\n**Indexes**\n\n");
+    if indexes.is_empty() {
+        out.push_str("_None_\n");
+    }
+    for (name, unique, index_columns) in indexes {
+        out.push_str(&format!(
+            "- {}{}: {}\n",
+            cell(&name),
+            if unique { " (UNIQUE)" } else { "" },
+            index_columns
+        ));
+    }
+
+    Ok(out)
+}
+
+#[tauri::command]
+async fn check_database_update_capability(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<DatabaseCapabilities, String> {
+    // SQLite silently falls back to read-only when the OS denies write access
+    let read_write = open_read_write(&state, &path).ok().filter(|conn| {
+        !conn
+            .is_readonly(rusqlite::DatabaseName::Main)
+            .unwrap_or(true)
+    });
+    let can_write = read_write.is_some();
+    let conn = match read_write {
+        Some(conn) => conn,
+        None => open_database_with_flags(
+            &state,
+            &path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(|e| e.to_string())?,
+    };
+
+    let can_read = conn
+        .query_row("SELECT count(*) FROM sqlite_master", [], |row| {
+            row.get::<_, i64>(0)
+        })
+        .is_ok();
+
+    // Creating a table needs the write lock as well as file access, roll back right away
+    let can_create_table = can_write
+        && conn
+            .execute_batch(
+                "BEGIN; CREATE TABLE IF NOT EXISTS _capability_test_ (id INTEGER); ROLLBACK;",
+            )
+            .is_ok();
+    // A failed CREATE stops the batch before its ROLLBACK
+    if !conn.is_autocommit() {
+        let _ = conn.execute_batch("ROLLBACK");
+    }
+
+    let is_wal_mode = can_read && get_journal_mode(&conn)?.eq_ignore_ascii_case("WAL");
+
+    Ok(DatabaseCapabilities {
+        can_read,
+        can_write,
+        can_create_table,
+        is_wal_mode,
+        sqlite_version: rusqlite::version().to_string(),
+    })
+}
+
+#[tauri::command]
+async fn get_connection_status(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<ConnectionStatus, String> {
+    let file_metadata = std::fs::metadata(&path).ok();
+    let file_exists = file_metadata.is_some();
+    let file_writable = file_metadata
+        .as_ref()
+        .is_some_and(|m| !m.permissions().readonly());
+    let file_size_bytes = file_metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+
+    // Read-only so a missing path isn't created as an empty database
+    let start = Instant::now();
+    let conn = open_database_with_flags(
+        &state,
+        &path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .ok()
+    .filter(|conn| {
+        conn.query_row("SELECT 1", [], |row| row.get::<_, i64>(0))
+            .is_ok()
+    });
+    let open_latency_ms = start.elapsed().as_millis() as u64;
+
+    // Pragmas read the header, so they fail on files that aren't SQLite databases
+    let journal_mode = conn
+        .as_ref()
+        .and_then(|conn| get_journal_mode(conn).ok())
+        .unwrap_or_default()
+        .to_uppercase();
+    let page_size = conn
+        .as_ref()
+        .and_then(|conn| {
+            conn.query_row("PRAGMA page_size", [], |row| row.get(0))
+                .ok()
+        })
+        .unwrap_or(0);
+
+    Ok(ConnectionStatus {
+        can_open: conn.is_some(),
+        sqlite_version: rusqlite::version().to_string(),
+        is_wal: journal_mode == "WAL",
+        journal_mode,
+        page_size,
+        file_exists,
+        file_writable,
+        file_size_bytes,
+        open_latency_ms,
+    })
+}
+
+// Probes with transactions that need progressively weaker locks; each attempt
+// gives up after 100 ms and is rolled back immediately, so nothing is written
+#[tauri::command]
+async fn get_write_lock_status(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<LockStatus, String> {
+    let conn = open_database_with_flags(
+        &state,
+        &path,
+        OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|e| e.to_string())?;
+    conn.busy_timeout(std::time::Duration::from_millis(100))
+        .map_err(|e| e.to_string())?;
+
+    let is_busy = |e: &rusqlite::Error| {
+        matches!(
+            e.sqlite_error_code(),
+            Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked)
+        )
+    };
+    let try_begin = |sql: &str| -> Result<bool, String> {
+        match conn.execute_batch(sql) {
+            Ok(()) => {
+                conn.execute_batch("ROLLBACK").map_err(|e| e.to_string())?;
+                Ok(true)
+            }
+            Err(e) if is_busy(&e) => Ok(false),
+            Err(e) => Err(e.to_string()),
+        }
+    };
+
+    if try_begin("BEGIN EXCLUSIVE")? {
+        return Ok(LockStatus {
+            is_locked: false,
+            lock_type: None,
+            suggested_wait_ms: 0,
+        });
+    }
+
+    // Only readers block EXCLUSIVE but not IMMEDIATE; a writer blocks both, and
+    // a pending or exclusive lock blocks reads too
+    let (lock_type, suggested_wait_ms) = if try_begin("BEGIN IMMEDIATE")? {
+        ("shared", 100)
+    } else {
+        match conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| {
+            row.get::<_, i64>(0)
+        }) {
+            Ok(_) => ("reserved", 500),
+            Err(e) if is_busy(&e) => ("exclusive", 1000),
+            Err(e) => return Err(e.to_string()),
+        }
+    };
+    Ok(LockStatus {
+        is_locked: true,
+        lock_type: Some(lock_type.to_string()),
+        suggested_wait_ms,
+    })
+}
+
+#[tauri::command]
+async fn get_trigger_details(
+    state: State<'_, AppState>,
+    path: String,
+    trigger_name: String,
+) -> Result<TriggerDetails, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let (table_name, sql): (String, String) = conn
+        .query_row(
+            "SELECT tbl_name, sql FROM sqlite_master WHERE type = 'trigger' AND name = ?1",
+            params![trigger_name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Trigger '{}' does not exist", trigger_name))?;
+
+    // Everything before BEGIN describes when the trigger fires, whitespace
+    // collapsed so keywords can be matched with single spaces
+    let words: Vec<&str> = sql.split_whitespace().collect();
+    let begin = words
+        .iter()
+        .position(|w| w.eq_ignore_ascii_case("BEGIN"))
+        .unwrap_or(words.len());
+    let header = words[..begin].join(" ");
+    let upper = format!("{} ", header.to_ascii_uppercase());
+
+    let timing = if upper.contains(" INSTEAD OF ") {
+        "INSTEAD OF"
+    } else if upper.contains(" AFTER ") {
+        "AFTER"
+    } else {
+        // BEFORE is also SQLite's default when no timing is given
+        "BEFORE"
+    };
+    let event = ["INSERT", "UPDATE", "DELETE"]
+        .into_iter()
+        .find(|e| upper.contains(&format!(" {} ", e)))
+        .unwrap_or_default();
+    let when_condition = upper
+        .find(" WHEN ")
+        .map(|pos| header[pos + " WHEN ".len()..].trim().to_string());
+    let for_each_row = upper.contains(" FOR EACH ROW ");
+
+    // EXPLAIN on the DDL itself fails because the trigger already exists, so
+    // explain a statement that fires it; the trigger body is compiled into a
+    // sub-program that starts with an Init carrying "-- TRIGGER name"
+    let table_q = quote_identifier(&table_name);
+    let firing_sql = match event {
+        "INSERT" => format!("INSERT INTO {} DEFAULT VALUES", table_q),
+        "DELETE" => format!("DELETE FROM {}", table_q),
+        _ => {
+            let assignments = get_column_names(&conn, &table_name)?
+                .iter()
+                .map(|c| format!("{0} = {0}", quote_identifier(c)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("UPDATE {} SET {}", table_q, assignments)
+        }
+    };
+    let mut stmt = conn
+        .prepare(&format!("EXPLAIN {}", firing_sql))
+        .map_err(|e| e.to_string())?;
+    let program = stmt
+        .query_map([], |row| {
+            let p4: rusqlite::types::Value = row.get(5)?;
+            Ok(ExplainOpcode {
+                addr: row.get(0)?,
+                opcode: row.get(1)?,
+                p1: row.get(2)?,
+                p2: row.get(3)?,
+                p3: row.get(4)?,
+                p4: match p4 {
+                    rusqlite::types::Value::Null => None,
+                    rusqlite::types::Value::Integer(i) => Some(i.to_string()),
+                    rusqlite::types::Value::Real(f) => Some(f.to_string()),
+                    rusqlite::types::Value::Text(s) => Some(s),
+                    rusqlite::types::Value::Blob(b) => Some(format!("{} byte blob", b.len())),
+                },
+                p5: row.get(6)?,
+                comment: row.get(7)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let marker = format!("-- TRIGGER {}", trigger_name);
+    let opcodes = match program
+        .iter()
+        .position(|op| op.addr == 0 && op.p4.as_deref() == Some(marker.as_str()))
+    {
+        Some(start) => {
+            let mut opcodes: Vec<ExplainOpcode> = program.into_iter().skip(start).collect();
+            let end = opcodes[1..]
+                .iter()
+                .position(|op| op.addr == 0)
+                .map_or(opcodes.len(), |i| i + 1);
+            opcodes.truncate(end);
+            opcodes
+        }
+        None => Vec::new(),
+    };
+
+    Ok(TriggerDetails {
+        name: trigger_name,
+        table_name,
+        timing: timing.to_string(),
+        event: event.to_string(),
+        when_condition,
+        for_each_row,
+        sql,
+        opcodes,
+    })
+}
+
+// Splits DDL into words, quoted names/strings and whole bracket-balanced
+// `( ... )` groups, so keywords inside expressions are never matched
+fn ddl_tokens(sql: &str) -> Vec<&str> {
+    let bytes = sql.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        match bytes[i] {
+            b if b.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            quote @ (b'\'' | b'"' | b'`' | b'[') => {
+                let close = if quote == b'[' { b']' } else { quote };
+                i += 1;
+                while i < bytes.len() {
+                    // A doubled quote is an escaped quote, not the end
+                    if bytes[i] == close && bytes.get(i + 1) != Some(&close) {
+                        break;
+                    }
+                    i += if bytes[i] == close { 2 } else { 1 };
+                }
+                i += 1;
+            }
+            b'(' => {
+                let mut depth = 0;
+                let mut quote: Option<u8> = None;
+                while i < bytes.len() {
+                    match (quote, bytes[i]) {
+                        (Some(q), c) if c == q => quote = None,
+                        (Some(_), _) => {}
+                        (None, c @ (b'\'' | b'"' | b'`')) => quote = Some(c),
+                        (None, b'(') => depth += 1,
+                        (None, b')') => {
+                            depth -= 1;
+                            if depth == 0 {
+                                i += 1;
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                    i += 1;
+                }
+            }
+            b if b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80 => {
+                while i < bytes.len()
+                    && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || bytes[i] >= 0x80)
+                {
+                    i += 1;
+                }
+            }
+            _ => i += 1,
+        }
+        let end = i.min(bytes.len());
+        tokens.push(&sql[start..end]);
+    }
+    tokens
+}
+
+fn paren_inner(token: &str) -> String {
+    token
+        .strip_prefix('(')
+        .and_then(|t| t.strip_suffix(')'))
+        .unwrap_or(token)
+        .trim()
+        .to_string()
+}
+
+fn parse_table_constraints(sql: &str) -> Vec<ConstraintInfo> {
+    let constraint = |kind: &str, expression: String| ConstraintInfo {
+        kind: kind.to_string(),
+        expression,
+    };
+
+    // The column and constraint list is the first parenthesized group
+    let Some(body) = ddl_tokens(sql).into_iter().find(|t| t.starts_with('(')) else {
+        return Vec::new();
+    };
+    let body = paren_inner(body);
+    let tokens = ddl_tokens(&body);
+
+    let mut constraints = Vec::new();
+    for definition in tokens.split(|t| *t == ",") {
+        let mut rest = definition;
+        if rest
+            .first()
+            .is_some_and(|t| t.eq_ignore_ascii_case("CONSTRAINT"))
+        {
+            rest = rest.get(2..).unwrap_or_default();
+        }
+        let Some(first) = rest.first() else {
+            continue;
+        };
+        let is_keyword =
+            |i: usize, word: &str| rest.get(i).is_some_and(|t| t.eq_ignore_ascii_case(word));
+
+        // Table-level constraints
+        if is_keyword(0, "PRIMARY") && is_keyword(1, "KEY") {
+            constraints.push(constraint(
+                "PRIMARY KEY",
+                rest.get(2).map(|t| paren_inner(t)).unwrap_or_default(),
+            ));
+            continue;
+        }
+        if is_keyword(0, "UNIQUE") {
+            constraints.push(constraint(
+                "UNIQUE",
+                rest.get(1).map(|t| paren_inner(t)).unwrap_or_default(),
+            ));
+            continue;
+        }
+        if is_keyword(0, "CHECK") {
+            constraints.push(constraint(
+                "CHECK",
+                rest.get(1).map(|t| paren_inner(t)).unwrap_or_default(),
+            ));
+            continue;
+        }
+        if is_keyword(0, "FOREIGN") {
+            continue;
+        }
+
+        // Column definition, constraints follow the name and type
+        let column = first.trim_matches(|c| matches!(c, '"' | '`' | '[' | ']'));
+        for i in 1..rest.len() {
+            if is_keyword(i, "PRIMARY") && is_keyword(i + 1, "KEY") {
+                constraints.push(constraint("PRIMARY KEY", column.to_string()));
+            } else if is_keyword(i, "UNIQUE") {
+                constraints.push(constraint("UNIQUE", column.to_string()));
+            } else if is_keyword(i, "CHECK") {
+                if let Some(group) = rest.get(i + 1) {
+                    constraints.push(constraint("CHECK", paren_inner(group)));
+                }
+            }
+        }
+    }
+    constraints
+}
+
+// Constraint kinds declared on a column, either in its own definition or in a
+// table-level constraint that names it
+fn column_constraint_kinds(sql: &str, column: &str) -> Vec<String> {
+    let Some(body) = ddl_tokens(sql).into_iter().find(|t| t.starts_with('(')) else {
+        return Vec::new();
+    };
+    let body = paren_inner(body);
+    let tokens = ddl_tokens(&body);
+    let is_column = |t: &str| {
+        t.trim_matches(|c| matches!(c, '"' | '`' | '[' | ']'))
+            .eq_ignore_ascii_case(column)
+    };
+    let mentions_column = |group: Option<&&str>| {
+        group.is_some_and(|g| ddl_tokens(&paren_inner(g)).into_iter().any(is_column))
+    };
+
+    let mut kinds: Vec<String> = Vec::new();
+    let mut add = |kind: &str| {
+        if !kinds.iter().any(|k| k == kind) {
+            kinds.push(kind.to_string());
+        }
+    };
+    for definition in tokens.split(|t| *t == ",") {
+        let mut rest = definition;
+        if rest
+            .first()
+            .is_some_and(|t| t.eq_ignore_ascii_case("CONSTRAINT"))
+        {
+            rest = rest.get(2..).unwrap_or_default();
+        }
+        let Some(first) = rest.first() else {
+            continue;
+        };
+        let is_keyword =
+            |i: usize, word: &str| rest.get(i).is_some_and(|t| t.eq_ignore_ascii_case(word));
+
+        if is_keyword(0, "PRIMARY") || is_keyword(0, "FOREIGN") {
+            if mentions_column(rest.get(2)) {
+                add(if is_keyword(0, "PRIMARY") {
+                    "PRIMARY KEY"
+                } else {
+                    "FOREIGN KEY"
+                });
+            }
+            continue;
+        }
+        if is_keyword(0, "UNIQUE") || is_keyword(0, "CHECK") {
+            if mentions_column(rest.get(1)) {
+                add(&first.to_ascii_uppercase());
+            }
+            continue;
+        }
+        if !is_column(first) {
+            continue;
+        }
+
+        for i in 1..rest.len() {
+            if is_keyword(i, "NOT") && is_keyword(i + 1, "NULL") {
+                add("NOT NULL");
+            } else if is_keyword(i, "PRIMARY") && is_keyword(i + 1, "KEY") {
+                add("PRIMARY KEY");
+            } else if is_keyword(i, "REFERENCES") {
+                add("FOREIGN KEY");
+            } else if is_keyword(i, "AS") {
+                add("GENERATED");
+            } else {
+                for kind in ["DEFAULT", "UNIQUE", "CHECK", "COLLATE"] {
+                    if is_keyword(i, kind) {
+                        add(kind);
+                    }
+                }
+            }
+        }
+    }
+    kinds
+}
+
+#[tauri::command]
+async fn get_table_constraints(
+    state: State<'_, AppState>,
+    path: String,
+    table: String,
+) -> Result<Vec<ConstraintInfo>, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let sql: String = conn
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            params![table],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Table '{}' does not exist", table))?;
+
+    Ok(parse_table_constraints(&sql))
+}
+
+#[tauri::command]
+async fn estimate_query_cost(
+    state: State<'_, AppState>,
+    path: String,
+    sql: String,
+) -> Result<QueryCostEstimate, String> {
+    // Read-only on top of EXPLAIN, so even a DML statement can't touch the data
+    let conn = open_database_with_flags(
+        &state,
+        &path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "EXPLAIN QUERY PLAN {}",
+            sql.trim().trim_end_matches(';')
+        ))
+        .map_err(|e| e.to_string())?;
+    let plan: Vec<(i64, i64, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(3)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut estimate = QueryCostEstimate {
+        plan_description: String::new(),
+        estimated_cost: 0,
+        uses_indexes: false,
+        involves_sorting: false,
+        involves_subquery: false,
+    };
+    let mut depths: HashMap<i64, usize> = HashMap::new();
+    for (id, parent, detail) in &plan {
+        let depth = depths.get(parent).map_or(0, |d| d + 1);
+        depths.insert(*id, depth);
+        estimate
+            .plan_description
+            .push_str(&format!("{}{}\n", "  ".repeat(depth), detail));
+
+        // Rough weights: a full scan dwarfs an index walk, which dwarfs a seek
+        let uses_index = detail.contains(" INDEX ") || detail.contains("PRIMARY KEY");
+        estimate.uses_indexes |= uses_index;
+        estimate.estimated_cost += if detail.starts_with("SCAN") && !uses_index {
+            1000
+        } else if detail.starts_with("SCAN") {
+            100
+        } else if detail.starts_with("SEARCH") {
+            10
+        } else if detail.contains("TEMP B-TREE") {
+            estimate.involves_sorting = true;
+            200
+        } else if detail.contains("SUBQUERY") || detail.starts_with("CO-ROUTINE") {
+            estimate.involves_subquery = true;
+            50
+        } else {
+            1
+        };
+    }
+
+    Ok(estimate)
+}
+
+#[tauri::command]
+async fn compute_referential_integrity_summary(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<IntegritySummary, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    // Only affects this connection, it's dropped when the command returns
+    conn.execute_batch("PRAGMA foreign_keys = ON")
+        .map_err(|e| e.to_string())?;
+
+    let tables_checked: u32 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%'",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare("SELECT \"table\", COUNT(*) FROM pragma_foreign_key_check GROUP BY \"table\"")
+        .map_err(|e| e.to_string())?;
+    let violations_by_table: HashMap<String, u64> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(IntegritySummary {
+        total_violations: violations_by_table.values().sum(),
+        tables_checked,
+        tables_with_violations: violations_by_table.len() as u32,
+        violations_by_table,
+    })
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct AnalysisProgress {
-    pub db_path: String,
-    pub progress: f64,
-    pub records_processed: u64,
-    pub total_records: u64,
-    pub time_remaining_secs: u64,
-    pub speed_records_per_sec: f64,
-    pub is_finished: bool,
+// Reports whether the linked SQLite was built with the session extension. The
+// bundled build isn't, so this is false and no session commands are exposed.
+#[tauri::command]
+async fn get_change_log_support(state: State<'_, AppState>, path: String) -> Result<bool, String> {
+    let conn = open_with_connection_prefs(&state, &path)?;
+    let mut stmt = conn
+        .prepare("PRAGMA compile_options")
+        .map_err(|e| e.to_string())?;
+    let options = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(options.iter().any(|o| o == "ENABLE_SESSION"))
 }
 
-pub struct AppState {
-    pub metadata_db_path: PathBuf,
-    pub analysis_tasks: Mutex<HashMap<String, Arc<AtomicBool>>>, // db_path to cancellation token
+#[tauri::command]
+async fn delete_database(state: State<'_, AppState>, id: i32) -> Result<(), String> {
+    let conn = get_metadata_conn(&state)?;
+    // Soft delete, the entry can be brought back with restore_database until purged
+    conn.execute(
+        "UPDATE metadata SET deleted_at = CURRENT_TIMESTAMP WHERE id = ?1",
+        params![id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TableInfo {
-    pub name: String,
-    pub row_count: i64,
+#[tauri::command]
+async fn set_database_icon(
+    state: State<'_, AppState>,
+    id: i32,
+    icon: Option<String>,
+) -> Result<(), String> {
+    validate_icon(icon.as_deref())?;
+    let conn = get_metadata_conn(&state)?;
+    let updated = conn
+        .execute(
+            "UPDATE metadata SET icon = ?1 WHERE id = ?2",
+            params![icon, id],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("No database with id {}", id));
+    }
+    Ok(())
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct DbStats {
-    pub total_tables: usize,
-    pub total_records: i64,
-    pub file_size_kb: u64,
+const MAX_NOTES_BYTES: usize = 64 * 1024;
+
+fn validate_notes(notes: &str) -> Result<(), String> {
+    if notes.len() > MAX_NOTES_BYTES {
+        return Err(format!(
+            "Notes must be at most {} KB",
+            MAX_NOTES_BYTES / 1024
+        ));
+    }
+    Ok(())
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TableData {
-    pub columns: Vec<String>,
-    pub rows: Vec<Vec<serde_json::Value>>,
-    pub total_pages: i64,
+#[tauri::command]
+async fn set_database_notes(
+    state: State<'_, AppState>,
+    id: i32,
+    notes: String,
+) -> Result<(), String> {
+    validate_notes(&notes)?;
+    let conn = get_metadata_conn(&state)?;
+    let updated = conn
+        .execute(
+            "UPDATE metadata SET notes = ?1 WHERE id = ?2",
+            params![notes, id],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("No database with id {}", id));
+    }
+    Ok(())
 }
 
-fn get_metadata_conn(state: &State<AppState>) -> Result<Connection, String> {
-    Connection::open(&state.metadata_db_path).map_err(|e| e.to_string())
+#[tauri::command]
+async fn get_database_notes(state: State<'_, AppState>, id: i32) -> Result<Option<String>, String> {
+    let conn = get_metadata_conn(&state)?;
+    conn.query_row(
+        "SELECT notes FROM metadata WHERE id = ?1",
+        params![id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("No database with id {}", id))
 }
 
 #[tauri::command]
-async fn import_database(
-    state: State<'_, AppState>,
-    name: String,
-    path: String,
-) -> Result<DatabaseMetadata, String> {
+async fn restore_database(state: State<'_, AppState>, id: i32) -> Result<DatabaseMetadata, String> {
     let conn = get_metadata_conn(&state)?;
+    let updated = conn
+        .execute(
+            "UPDATE metadata SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+            params![id],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("No deleted database with id {}", id));
+    }
 
-    // Check if it's a valid sqlite database
-    let _test_conn =
-        Connection::open(&path).map_err(|e| format!("Invalid SQLite database: {}", e))?;
+    conn.query_row(
+        &format!("SELECT {} FROM metadata WHERE id = ?1", METADATA_COLUMNS),
+        params![id],
+        metadata_from_row,
+    )
+    .map_err(|e| e.to_string())
+}
 
-    conn.execute(
-        "INSERT OR REPLACE INTO metadata (name, path, last_accessed) VALUES (?1, ?2, CURRENT_TIMESTAMP)",
-        params![name, path],
-    ).map_err(|e| e.to_string())?;
+#[tauri::command]
+async fn purge_deleted_databases(state: State<'_, AppState>) -> Result<u32, String> {
+    let mut conn = get_metadata_conn(&state)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "DELETE FROM column_annotations WHERE db_path IN (SELECT path FROM metadata WHERE deleted_at IS NOT NULL)",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.execute(
+        "DELETE FROM table_row_count_cache WHERE db_path IN (SELECT path FROM metadata WHERE deleted_at IS NOT NULL)",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+    let purged = tx
+        .execute("DELETE FROM metadata WHERE deleted_at IS NOT NULL", [])
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(purged as u32)
+}
 
-    let mut stmt = conn.prepare("SELECT id, name, path, created_at, last_accessed, analysis_results FROM metadata WHERE path = ?1")
+#[tauri::command]
+async fn get_app_data_usage(state: State<'_, AppState>) -> Result<AppDataUsage, String> {
+    let metadata_db_size_bytes = std::fs::metadata(&state.metadata_db_path)
+        .map(|m| m.len())
         .map_err(|e| e.to_string())?;
 
-    let meta = stmt
-        .query_row(params![path], |row| {
-            Ok(DatabaseMetadata {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                path: row.get(2)?,
-                created_at: row.get(3)?,
-                last_accessed: row.get(4)?,
-                analysis_results: row.get(5)?,
-            })
-        })
+    // Soft-deleted entries are counted too, they take up space until purged
+    let conn = get_metadata_conn(&state)?;
+    let (databases_tracked, databases_with_analysis, total_analysis_json_bytes, oldest_entry_date) =
+        conn.query_row(
+            "SELECT COUNT(*), COUNT(analysis_results), COALESCE(SUM(LENGTH(CAST(analysis_results AS BLOB))), 0), MIN(created_at) FROM metadata",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
         .map_err(|e| e.to_string())?;
 
-    Ok(meta)
+    Ok(AppDataUsage {
+        metadata_db_size_bytes,
+        databases_tracked,
+        databases_with_analysis,
+        total_analysis_json_bytes,
+        oldest_entry_date,
+    })
 }
 
 #[tauri::command]
-async fn list_databases(state: State<'_, AppState>) -> Result<Vec<DatabaseMetadata>, String> {
+async fn shrink_metadata_db(state: State<'_, AppState>) -> Result<ShrinkResult, String> {
+    let size_before_bytes = std::fs::metadata(&state.metadata_db_path)
+        .map(|m| m.len())
+        .map_err(|e| e.to_string())?;
+
     let conn = get_metadata_conn(&state)?;
-    let mut stmt = conn.prepare("SELECT id, name, path, created_at, last_accessed, analysis_results FROM metadata ORDER BY last_accessed DESC")
+    conn.execute_batch("VACUUM").map_err(|e| e.to_string())?;
+    drop(conn);
+
+    let size_after_bytes = std::fs::metadata(&state.metadata_db_path)
+        .map(|m| m.len())
         .map_err(|e| e.to_string())?;
 
-    let db_iter = stmt
+    Ok(ShrinkResult {
+        size_before_bytes,
+        size_after_bytes,
+        saved_bytes: size_before_bytes.saturating_sub(size_after_bytes),
+    })
+}
+
+// Newest first, so the first two entries are the current and the previous version
+#[tauri::command]
+async fn get_app_version_history(
+    state: State<'_, AppState>,
+) -> Result<Vec<VersionHistoryEntry>, String> {
+    let conn = get_metadata_conn(&state)?;
+    let mut stmt = conn
+        .prepare("SELECT id, version, first_seen_at FROM version_history ORDER BY id DESC")
+        .map_err(|e| e.to_string())?;
+    let entries = stmt
         .query_map([], |row| {
-            Ok(DatabaseMetadata {
+            Ok(VersionHistoryEntry {
                 id: row.get(0)?,
-                name: row.get(1)?,
-                path: row.get(2)?,
-                created_at: row.get(3)?,
-                last_accessed: row.get(4)?,
-                analysis_results: row.get(5)?,
+                version: row.get(1)?,
+                first_seen_at: row.get(2)?,
             })
         })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())?;
 
-    let mut dbs = Vec::new();
-    for db in db_iter {
-        dbs.push(db.map_err(|e| e.to_string())?);
-    }
-    Ok(dbs)
+    Ok(entries)
 }
 
 #[tauri::command]
-async fn stop_db_analysis(state: State<'_, AppState>, path: String) -> Result<(), String> {
-    let mut tasks = state.analysis_tasks.lock().unwrap();
-    if let Some(token) = tasks.remove(&path) {
-        token.store(true, Ordering::SeqCst);
-    }
+async fn export_metadata_backup(
+    state: State<'_, AppState>,
+    output_path: String,
+) -> Result<(), String> {
+    std::fs::copy(&state.metadata_db_path, &output_path).map_err(|e| e.to_string())?;
     Ok(())
 }
 
 #[tauri::command]
-async fn start_db_analysis(
-    app: tauri::AppHandle,
+async fn export_metadata_as_csv(
     state: State<'_, AppState>,
-    path: String,
-) -> Result<(), String> {
-    let path_clone = path.clone();
-    let cancellation_token = Arc::new(AtomicBool::new(false));
+    output_path: String,
+) -> Result<u64, String> {
+    let conn = get_metadata_conn(&state)?;
+    // analysis_results is a large JSON blob that doesn't belong in a spreadsheet
+    let columns: Vec<String> = get_column_names(&conn, "metadata")?
+        .into_iter()
+        .filter(|c| c != "analysis_results")
+        .collect();
 
-    {
-        let mut tasks = state.analysis_tasks.lock().unwrap();
-        // If a task is already running for this path, stop it first
-        if let Some(old_token) = tasks.get(&path) {
-            old_token.store(true, Ordering::SeqCst);
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM metadata ORDER BY id",
+            columns
+                .iter()
+                .map(|c| quote_identifier(c))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+
+    let mut writer = csv::Writer::from_path(&output_path).map_err(|e| e.to_string())?;
+    writer.write_record(&columns).map_err(|e| e.to_string())?;
+    let mut written = 0u64;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let mut record = Vec::with_capacity(columns.len());
+        for i in 0..columns.len() {
+            let value = match row
+                .get::<_, rusqlite::types::Value>(i)
+                .map_err(|e| e.to_string())?
+            {
+                rusqlite::types::Value::Null => String::new(),
+                rusqlite::types::Value::Integer(n) => n.to_string(),
+                rusqlite::types::Value::Real(f) => f.to_string(),
+                rusqlite::types::Value::Text(t) => t,
+                rusqlite::types::Value::Blob(b) => String::from_utf8_lossy(&b).into_owned(),
+            };
+            record.push(value);
         }
-        tasks.insert(path.clone(), cancellation_token.clone());
+        writer.write_record(&record).map_err(|e| e.to_string())?;
+        written += 1;
     }
+    writer.flush().map_err(|e| e.to_string())?;
 
-    let metadata_db_path = state.metadata_db_path.clone();
+    Ok(written)
+}
 
-    tauri::async_runtime::spawn(async move {
-        log_debug(
-            "Starting background analysis",
-            serde_json::json!({"path": path_clone}),
-            "B",
-        );
-        let result = analyze_database_internal(&app, &path_clone, cancellation_token).await;
+// Everything known about one database in a single object, for documentation or sharing
+#[tauri::command]
+async fn export_database_metadata_as_json(
+    state: State<'_, AppState>,
+    db_path: String,
+) -> Result<serde_json::Value, String> {
+    let conn = get_metadata_conn(&state)?;
+    let metadata = get_metadata_by_path(&conn, &db_path)?;
 
-        // Remove task from active tasks
-        if let Some(state) = app.try_state::<AppState>() {
-            let mut tasks = state.analysis_tasks.lock().unwrap();
-            tasks.remove(&path_clone);
+    let analysis_results = match &metadata.analysis_results {
+        Some(json) => serde_json::from_str(json).map_err(|e| e.to_string())?,
+        None => serde_json::Value::Null,
+    };
+
+    let mut stmt = conn
+        .prepare("SELECT table_name, column_name, description FROM column_annotations WHERE db_path = ?1 ORDER BY table_name, column_name")
+        .map_err(|e| e.to_string())?;
+    let mut annotations = serde_json::Map::new();
+    let rows = stmt
+        .query_map(params![db_path], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    for row in rows {
+        let (table_name, column_name, description) = row.map_err(|e| e.to_string())?;
+        if let serde_json::Value::Object(columns) = annotations
+            .entry(table_name)
+            .or_insert_with(|| serde_json::json!({}))
+        {
+            columns.insert(column_name, serde_json::Value::String(description));
         }
+    }
 
-        match result {
-            Ok(analysis) => {
-                log_debug(
-                    "Analysis finished successfully",
-                    serde_json::json!({"path": path_clone}),
-                    "B",
-                );
-                // Save results to metadata DB
-                if let Ok(conn) = Connection::open(&metadata_db_path) {
-                    let json_results = serde_json::to_string(&analysis).unwrap_or_default();
-                    let _ = conn.execute(
-                        "UPDATE metadata SET analysis_results = ?1 WHERE path = ?2",
-                        params![json_results, path_clone],
-                    );
-                }
-            }
-            Err(e) => {
-                log_debug(
-                    "Analysis failed or cancelled",
-                    serde_json::json!({"path": path_clone, "error": e}),
-                    "B",
-                );
+    // Read-only so a missing file isn't recreated as an empty database
+    let schema = if metadata.file_exists && !metadata.is_encrypted {
+        let db_conn = open_database_with_flags(
+            &state,
+            &db_path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(|e| e.to_string())?;
+        database_schema_json(&db_conn)?
+    } else {
+        serde_json::Value::Null
+    };
+
+    Ok(serde_json::json!({
+        "metadata": metadata,
+        "schema": schema,
+        "analysis_results": analysis_results,
+        "annotations": annotations,
+    }))
+}
+
+// Accepts the object produced by export_database_metadata_as_json; the schema
+// part is informational and ignored, it is read from the file itself
+#[tauri::command]
+async fn import_metadata_from_json(
+    state: State<'_, AppState>,
+    json_blob: serde_json::Value,
+) -> Result<DatabaseMetadata, String> {
+    let metadata = &json_blob["metadata"];
+    let original_path = metadata["path"]
+        .as_str()
+        .ok_or("Missing required field 'metadata.path'")?;
+    // Exports from another machine may point at files that don't exist here,
+    // those keep their path as written
+    let canonical = canonical_db_path(original_path).ok();
+    let path = canonical.as_deref().unwrap_or(original_path);
+    let name = metadata["name"]
+        .as_str()
+        .map(str::to_string)
+        .or_else(|| {
+            Path::new(path)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| path.to_string());
+    let icon = metadata["icon"].as_str();
+    validate_icon(icon)?;
+    let notes = metadata["notes"].as_str();
+    if let Some(notes) = notes {
+        validate_notes(notes)?;
+    }
+    let is_encrypted = metadata["is_encrypted"].as_bool().unwrap_or(false);
+    let analysis_results = match &json_blob["analysis_results"] {
+        serde_json::Value::Null => None,
+        results => {
+            // Reject results that the analysis view couldn't load later
+            serde_json::from_value::<AnalysisResults>(results.clone())
+                .map_err(|e| format!("Invalid analysis_results: {}", e))?;
+            Some(results.to_string())
+        }
+    };
+
+    let mut conn = get_metadata_conn(&state)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    if canonical.is_some() {
+        adopt_canonical_path(&tx, path)?;
+    }
+    tx.execute(
+        "INSERT INTO metadata (name, path, last_accessed, analysis_results, is_encrypted, file_hash, icon, notes) VALUES (?1, ?2, CURRENT_TIMESTAMP, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(path) DO UPDATE SET name = excluded.name, analysis_results = excluded.analysis_results, is_encrypted = excluded.is_encrypted, file_hash = excluded.file_hash, icon = excluded.icon, notes = excluded.notes, deleted_at = NULL",
+        params![
+            name,
+            path,
+            analysis_results,
+            is_encrypted,
+            file_hash(path).ok(),
+            icon,
+            notes
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    if let Some(tables) = json_blob["annotations"].as_object() {
+        for (table_name, columns) in tables {
+            for (column_name, description) in columns.as_object().into_iter().flatten() {
+                let Some(description) = description.as_str() else {
+                    continue;
+                };
+                tx.execute(
+                    "INSERT OR REPLACE INTO column_annotations (db_path, table_name, column_name, description, updated_at) VALUES (?1, ?2, ?3, ?4, CURRENT_TIMESTAMP)",
+                    params![path, table_name, column_name, description],
+                )
+                .map_err(|e| e.to_string())?;
             }
         }
-    });
+    }
 
-    Ok(())
+    let imported = get_metadata_by_path(&tx, path)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(imported)
 }
 
-async fn analyze_database_internal(
-    app: &tauri::AppHandle,
-    db_path: &str,
-    cancel: Arc<AtomicBool>,
-) -> Result<AnalysisResults, String> {
-    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+fn database_schema_json(conn: &Connection) -> Result<serde_json::Value, String> {
+    let mut stmt = conn
+        .prepare("SELECT name, sql FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name")
+        .map_err(|e| e.to_string())?;
+    let tables: Vec<(String, Option<String>)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
 
-    // Get all tables and their row counts
+    let mut schema = Vec::with_capacity(tables.len());
+    for (table, sql) in tables {
+        let mut stmt = conn
+            .prepare("SELECT il.name, il.\"unique\", il.origin, (SELECT sql FROM sqlite_master WHERE type = 'index' AND name = il.name) FROM pragma_index_list(?1) il ORDER BY il.name")
+            .map_err(|e| e.to_string())?;
+        let indexes = stmt
+            .query_map(params![table], |row| {
+                Ok(serde_json::json!({
+                    "name": row.get::<_, String>(0)?,
+                    "unique": row.get::<_, bool>(1)?,
+                    "origin": row.get::<_, String>(2)?,
+                    "sql": row.get::<_, Option<String>>(3)?,
+                }))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        let mut stmt = conn
+            .prepare("SELECT id, \"table\", \"from\", \"to\", on_update, on_delete FROM pragma_foreign_key_list(?1) ORDER BY id, seq")
+            .map_err(|e| e.to_string())?;
+        let foreign_keys = stmt
+            .query_map(params![table], |row| {
+                Ok(serde_json::json!({
+                    "id": row.get::<_, i64>(0)?,
+                    "parent_table": row.get::<_, String>(1)?,
+                    "from": row.get::<_, String>(2)?,
+                    "to": row.get::<_, Option<String>>(3)?,
+                    "on_update": row.get::<_, String>(4)?,
+                    "on_delete": row.get::<_, String>(5)?,
+                }))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        let mut stmt = conn
+            .prepare("SELECT name, sql FROM sqlite_master WHERE type = 'trigger' AND tbl_name = ?1 ORDER BY name")
+            .map_err(|e| e.to_string())?;
+        let triggers = stmt
+            .query_map(params![table], |row| {
+                Ok(serde_json::json!({
+                    "name": row.get::<_, String>(0)?,
+                    "sql": row.get::<_, Option<String>>(1)?,
+                }))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        schema.push(serde_json::json!({
+            "name": table,
+            "sql": sql,
+            "columns": get_table_columns(conn, &table)?,
+            "indexes": indexes,
+            "foreign_keys": foreign_keys,
+            "triggers": triggers,
+        }));
+    }
+
+    Ok(serde_json::json!({ "tables": schema }))
+}
+
+// Writes a single HTML file with inline CSS and SVG charts, so it opens anywhere
+// without the app or network access
+#[tauri::command]
+async fn export_analysis_report_html(
+    state: State<'_, AppState>,
+    db_path: String,
+    output_path: String,
+) -> Result<(), String> {
+    let metadata = get_metadata_by_path(&get_metadata_conn(&state)?, &db_path)?;
+    let analysis: Option<AnalysisResults> = match &metadata.analysis_results {
+        Some(json) => Some(serde_json::from_str(json).map_err(|e| e.to_string())?),
+        None => None,
+    };
+    let stats = get_db_stats(state.clone(), db_path.clone()).await?;
+
+    let conn = open_database_with_flags(
+        &state,
+        &db_path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|e| e.to_string())?;
     let mut stmt = conn
-        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%'")
+        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name")
         .map_err(|e| e.to_string())?;
     let tables: Vec<String> = stmt
         .query_map([], |row| row.get(0))
@@ -246,287 +7130,544 @@ async fn analyze_database_internal(
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())?;
 
-    let mut total_records = 0;
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!(
+        "<title>{} - Analysis Report</title>\n",
+        html_escape(&metadata.name)
+    ));
+    html.push_str(
+        "<style>
+body { font-family: -apple-system, 'Segoe UI', Roboto, sans-serif; margin: 2rem auto; max-width: 960px; color: #1f2937; }
+h1 { margin-bottom: 0.25rem; }
+h2 { margin-top: 2.5rem; border-bottom: 1px solid #e5e7eb; padding-bottom: 0.25rem; }
+table { border-collapse: collapse; width: 100%; margin: 0.75rem 0; font-size: 0.9rem; }
+th, td { border: 1px solid #e5e7eb; padding: 0.4rem 0.6rem; text-align: left; vertical-align: top; }
+th { background: #f9fafb; }
+.muted { color: #6b7280; }
+.num { text-align: right; font-variant-numeric: tabular-nums; }
+</style>\n</head>\n<body>\n",
+    );
+
+    html.push_str(&format!("<h1>{}</h1>\n", html_escape(&metadata.name)));
+    html.push_str(&format!(
+        "<p class=\"muted\">{}<br>Imported {} &middot; report generated {}</p>\n",
+        html_escape(&metadata.path),
+        html_escape(&metadata.created_at),
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+    ));
+
+    html.push_str("<h2>Overview</h2>\n<table>\n");
+    let overview = [
+        ("Tables", stats.total_tables.to_string()),
+        ("Records", stats.total_records.to_string()),
+        ("File size", format!("{} KB", stats.file_size_kb)),
+        (
+            "Page size",
+            stats
+                .page_size
+                .map(|p| format!("{} bytes", p))
+                .unwrap_or_else(|| "-".into()),
+        ),
+        (
+            "Fragmentation",
+            stats
+                .fragmentation_percent
+                .map(|f| format!("{:.1}%", f))
+                .unwrap_or_else(|| "-".into()),
+        ),
+        ("Encoding", stats.encoding.clone()),
+        ("Journal mode", stats.journal_mode.clone()),
+    ];
+    for (label, value) in overview {
+        html.push_str(&format!(
+            "<tr><th>{}</th><td>{}</td></tr>\n",
+            label,
+            html_escape(&value)
+        ));
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>Tables</h2>\n<table>\n<tr><th>Table</th><th class=\"num\">Rows</th><th class=\"num\">Columns</th><th class=\"num\">Indexes</th></tr>\n");
     for table in &tables {
-        let count: u64 = conn
-            .query_row(&format!("SELECT COUNT(*) FROM \"{}\"", table), [], |row| {
-                row.get(0)
-            })
+        let row_count: i64 = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM {}", quote_identifier(table)),
+                [],
+                |row| row.get(0),
+            )
             .unwrap_or(0);
-        total_records += count;
+        let index_count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_index_list(?1)",
+                params![table],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        html.push_str(&format!(
+            "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>\n",
+            html_escape(table),
+            row_count,
+            get_table_columns(&conn, table)?.len(),
+            index_count
+        ));
     }
+    html.push_str("</table>\n");
 
-    let mut results = AnalysisResults::default();
-    let mut records_processed = 0;
-    let start_time = Instant::now();
-
+    html.push_str("<h2>Schema</h2>\n");
     for table in &tables {
-        if cancel.load(Ordering::SeqCst) {
-            return Err("Analysis cancelled".into());
+        html.push_str(&format!(
+            "<h3>{}</h3>\n<table>\n<tr><th>Column</th><th>Type</th><th>Nullable</th><th>Default</th><th>Key</th></tr>\n",
+            html_escape(table)
+        ));
+        for col in get_table_columns(&conn, table)? {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                html_escape(&col.name),
+                html_escape(&col.declared_type),
+                if col.not_null { "NO" } else { "YES" },
+                html_escape(col.default_value.as_deref().unwrap_or("")),
+                if col.primary_key > 0 { "PK" } else { "" }
+            ));
         }
+        html.push_str("</table>\n");
 
         let mut stmt = conn
-            .prepare(&format!("SELECT * FROM \"{}\"", table))
+            .prepare("SELECT il.name, il.\"unique\", (SELECT group_concat(coalesce(ii.name, '<expression>'), ', ') FROM (SELECT name FROM pragma_index_xinfo(il.name) WHERE key ORDER BY seqno) ii) FROM pragma_index_list(?1) il ORDER BY il.name")
+            .map_err(|e| e.to_string())?;
+        let indexes: Vec<(String, bool, String)> = stmt
+            .query_map(params![table], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
             .map_err(|e| e.to_string())?;
-        let columns: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
-        let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+        if !indexes.is_empty() {
+            html.push_str("<p class=\"muted\">Indexes:</p>\n<ul>\n");
+            for (name, unique, columns) in indexes {
+                html.push_str(&format!(
+                    "<li>{}{}: {}</li>\n",
+                    html_escape(&name),
+                    if unique { " (UNIQUE)" } else { "" },
+                    html_escape(&columns)
+                ));
+            }
+            html.push_str("</ul>\n");
+        }
+    }
 
-        while let Some(row) = rows.next().map_err(|e| e.to_string())? {
-            if cancel.load(Ordering::SeqCst) {
-                return Err("Analysis cancelled".into());
+    html.push_str("<h2>Analysis</h2>\n");
+    match &analysis {
+        None => html.push_str("<p class=\"muted\">This database has not been analyzed yet.</p>\n"),
+        Some(analysis) => {
+            html.push_str(&format!(
+                "<p>{} characters analyzed.</p>\n",
+                analysis.total_chars
+            ));
+            for warning in &analysis.warnings {
+                html.push_str(&format!(
+                    "<p class=\"muted\">Warning: {}</p>\n",
+                    html_escape(warning)
+                ));
             }
+            html.push_str("<h3>Type distribution</h3>\n");
+            html.push_str(&type_distribution_svg(&analysis.type_distribution));
+            html.push_str("<h3>Most frequent characters</h3>\n");
+            html.push_str(&char_frequency_svg(&analysis.char_frequency));
+        }
+    }
 
-            for (i, col_name) in columns.iter().enumerate() {
-                let value: rusqlite::types::Value =
-                    row.get(i).unwrap_or(rusqlite::types::Value::Null);
-                match value {
-                    rusqlite::types::Value::Text(s) => {
-                        results.total_chars += s.chars().count() as u64;
-                        for c in s.chars() {
-                            *results.char_frequency.entry(c as u32).or_insert(0) += 1;
-                            if c.is_numeric() {
-                                results.type_distribution.numeric += 1;
-                            } else if c.is_alphabetic() {
-                                results.type_distribution.alphabets += 1;
-                            } else {
-                                results.type_distribution.special += 1;
-                            }
-                        }
+    html.push_str("</body>\n</html>\n");
+    std::fs::write(&output_path, html).map_err(|e| e.to_string())
+}
 
-                        // Simple format detection
-                        let format_key = format!("{}.{}", table, col_name);
-                        let formats = results
-                            .column_formats
-                            .entry(format_key)
-                            .or_insert_with(Vec::new);
-                        if s.contains('@')
-                            && s.contains('.')
-                            && !formats.contains(&"Email".to_string())
-                        {
-                            formats.push("Email".into());
-                        }
-                        if (s.starts_with("http") || s.starts_with("www"))
-                            && !formats.contains(&"URL".to_string())
-                        {
-                            formats.push("URL".into());
-                        }
-                    }
-                    rusqlite::types::Value::Integer(_) | rusqlite::types::Value::Real(_) => {
-                        results.type_distribution.numeric += 1;
-                    }
-                    rusqlite::types::Value::Blob(b) => {
-                        results.total_chars += b.len() as u64;
-                        results.type_distribution.unknown += 1;
-                    }
-                    rusqlite::types::Value::Null => {}
-                }
-            }
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+const REPORT_COLORS: [&str; 4] = ["#3b82f6", "#10b981", "#f59e0b", "#9ca3af"];
+
+fn type_distribution_svg(distribution: &TypeDistribution) -> String {
+    let slices = [
+        ("Numeric", distribution.numeric),
+        ("Alphabets", distribution.alphabets),
+        ("Special", distribution.special),
+        ("Unknown", distribution.unknown),
+    ];
+    let total: u64 = slices.iter().map(|(_, count)| count).sum();
+    if total == 0 {
+        return "<p class=\"muted\">No values.</p>\n".to_string();
+    }
+
+    let (cx, cy, r) = (110.0, 110.0, 100.0);
+    let mut svg = String::from(
+        "<svg width=\"420\" height=\"220\" viewBox=\"0 0 420 220\" xmlns=\"http://www.w3.org/2000/svg\">\n",
+    );
+    // Angles start at 12 o'clock and run clockwise
+    let mut angle = -std::f64::consts::FRAC_PI_2;
+    for (i, (label, count)) in slices.iter().enumerate() {
+        let fraction = *count as f64 / total as f64;
+        let color = REPORT_COLORS[i];
+        if fraction >= 1.0 {
+            // A single full slice can't be drawn as an arc
+            svg.push_str(&format!(
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>\n",
+                cx, cy, r, color
+            ));
+        } else if fraction > 0.0 {
+            let end = angle + fraction * std::f64::consts::TAU;
+            svg.push_str(&format!(
+                "<path d=\"M {cx} {cy} L {:.2} {:.2} A {r} {r} 0 {} 1 {:.2} {:.2} Z\" fill=\"{color}\"/>\n",
+                cx + r * angle.cos(),
+                cy + r * angle.sin(),
+                if fraction > 0.5 { 1 } else { 0 },
+                cx + r * end.cos(),
+                cy + r * end.sin(),
+            ));
+            angle = end;
+        }
+        svg.push_str(&format!(
+            "<rect x=\"240\" y=\"{}\" width=\"14\" height=\"14\" fill=\"{}\"/><text x=\"262\" y=\"{}\" font-size=\"13\">{} {} ({:.1}%)</text>\n",
+            50 + i * 30,
+            color,
+            62 + i * 30,
+            label,
+            count,
+            fraction * 100.0
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
 
-            records_processed += 1;
+fn char_frequency_svg(char_frequency: &HashMap<u32, u64>) -> String {
+    let mut top: Vec<(u32, u64)> = char_frequency.iter().map(|(c, n)| (*c, *n)).collect();
+    top.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    top.truncate(20);
+    let Some(max) = top.first().map(|(_, count)| *count) else {
+        return "<p class=\"muted\">No text values.</p>\n".to_string();
+    };
 
-            // Emit progress every 100 records or so to not flood the frontend
-            if records_processed % 100 == 0 || records_processed == total_records {
-                let elapsed = start_time.elapsed().as_secs_f64();
-                let speed = if elapsed > 0.0 {
-                    records_processed as f64 / elapsed
-                } else {
-                    0.0
-                };
-                let remaining = if speed > 0.0 {
-                    (total_records - records_processed) as f64 / speed
-                } else {
-                    0.0
-                };
+    let bar_height = 18;
+    let mut svg = format!(
+        "<svg width=\"640\" height=\"{0}\" viewBox=\"0 0 640 {0}\" xmlns=\"http://www.w3.org/2000/svg\">\n",
+        top.len() * (bar_height + 4)
+    );
+    for (i, (code, count)) in top.iter().enumerate() {
+        let y = i * (bar_height + 4);
+        // Whitespace and control characters are shown by code point
+        let label = match char::from_u32(*code) {
+            Some(c) if !c.is_whitespace() && !c.is_control() => html_escape(&c.to_string()),
+            _ => format!("U+{:04X}", code),
+        };
+        let width = (*count as f64 / max as f64) * 480.0;
+        svg.push_str(&format!(
+            "<text x=\"50\" y=\"{}\" font-size=\"13\" text-anchor=\"end\">{}</text><rect x=\"60\" y=\"{}\" width=\"{:.1}\" height=\"{}\" fill=\"{}\"/><text x=\"{:.1}\" y=\"{}\" font-size=\"12\">{}</text>\n",
+            y + 14,
+            label,
+            y,
+            width,
+            bar_height,
+            REPORT_COLORS[0],
+            66.0 + width,
+            y + 14,
+            count
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
 
-                let _ = app.emit(
-                    "analysis-progress",
-                    AnalysisProgress {
-                        db_path: db_path.to_string(),
-                        progress: (records_processed as f64 / total_records as f64) * 100.0,
-                        records_processed,
-                        total_records,
-                        time_remaining_secs: remaining as u64,
-                        speed_records_per_sec: speed,
-                        is_finished: records_processed == total_records,
-                    },
-                );
+#[tauri::command]
+async fn restore_metadata_backup(
+    state: State<'_, AppState>,
+    input_path: String,
+) -> Result<(), String> {
+    {
+        let backup_conn =
+            Connection::open_with_flags(&input_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .map_err(|e| format!("Invalid SQLite database: {}", e))?;
 
-                if records_processed % 1000 == 0 || records_processed == total_records {
-                    log_debug(
-                        "Analysis progress update",
-                        serde_json::json!({
-                            "db_path": db_path,
-                            "progress": (records_processed as f64 / total_records as f64) * 100.0,
-                            "records": records_processed,
-                            "total": total_records
-                        }),
-                        "C",
-                    );
-                }
+        // Opening is lazy, so touch the schema to make sure this really is a SQLite file
+        let columns = get_column_names(&backup_conn, "metadata")
+            .map_err(|e| format!("Invalid SQLite database: {}", e))?;
+        for required in ["id", "name", "path", "created_at", "last_accessed"] {
+            if !columns.iter().any(|c| c == required) {
+                return Err(format!(
+                    "Backup is missing the expected metadata table (no '{}' column)",
+                    required
+                ));
             }
         }
     }
 
-    Ok(results)
+    std::fs::copy(&input_path, &state.metadata_db_path).map_err(|e| e.to_string())?;
+
+    // Older backups may predate some columns, bring them up to date
+    let conn = get_metadata_conn(&state)?;
+    migrate_metadata_db(&conn)?;
+    *state.preferences.lock().unwrap_or_else(|e| e.into_inner()) =
+        load_app_preferences(&conn).unwrap_or_default();
+    Ok(())
 }
 
 #[tauri::command]
-async fn get_tables(path: String) -> Result<Vec<TableInfo>, String> {
-    let conn = Connection::open(&path).map_err(|e| e.to_string())?;
-    let mut stmt = conn
-        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%'")
-        .map_err(|e| e.to_string())?;
-
-    let table_names = stmt
-        .query_map([], |row| row.get::<_, String>(0))
+async fn annotate_column(
+    state: State<'_, AppState>,
+    db_path: String,
+    table_name: String,
+    column_name: String,
+    description: String,
+) -> Result<(), String> {
+    let conn = get_metadata_conn(&state)?;
+    // An empty description clears the annotation
+    if description.trim().is_empty() {
+        conn.execute(
+            "DELETE FROM column_annotations WHERE db_path = ?1 AND table_name = ?2 AND column_name = ?3",
+            params![db_path, table_name, column_name],
+        )
         .map_err(|e| e.to_string())?;
-
-    let mut tables = Vec::new();
-    for name_result in table_names {
-        let name = name_result.map_err(|e| e.to_string())?;
-        let row_count: i64 = conn
-            .query_row(&format!("SELECT COUNT(*) FROM \"{}\"", name), [], |row| {
-                row.get(0)
-            })
-            .unwrap_or(0);
-
-        tables.push(TableInfo { name, row_count });
+        return Ok(());
     }
-    Ok(tables)
+
+    conn.execute(
+        "INSERT OR REPLACE INTO column_annotations (db_path, table_name, column_name, description, updated_at) VALUES (?1, ?2, ?3, ?4, CURRENT_TIMESTAMP)",
+        params![db_path, table_name, column_name, description],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
 }
 
 #[tauri::command]
-async fn get_table_data(
-    path: String,
-    table: String,
-    page: i64,
-    page_size: i64,
-    search: Option<String>,
-) -> Result<TableData, String> {
-    let conn = Connection::open(&path).map_err(|e| e.to_string())?;
-
-    // Get columns
+async fn get_column_annotations(
+    state: State<'_, AppState>,
+    db_path: String,
+    table_name: String,
+) -> Result<HashMap<String, String>, String> {
+    let conn = get_metadata_conn(&state)?;
     let mut stmt = conn
-        .prepare(&format!("PRAGMA table_info(\"{}\")", table))
+        .prepare("SELECT column_name, description FROM column_annotations WHERE db_path = ?1 AND table_name = ?2")
         .map_err(|e| e.to_string())?;
-    let columns: Vec<String> = stmt
-        .query_map([], |row| row.get::<_, String>(1))
+    let annotations = stmt
+        .query_map(params![db_path, table_name], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
         .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
+        .collect::<Result<HashMap<_, _>, _>>()
         .map_err(|e| e.to_string())?;
 
-    // Prepare search condition
-    let where_clause = if let Some(ref s) = search {
-        if s.is_empty() {
-            String::new()
-        } else {
-            let search_parts: Vec<String> = columns
-                .iter()
-                .map(|col| format!("\"{}\" LIKE '%{}%'", col, s.replace("'", "''")))
-                .collect();
-            format!(" WHERE {}", search_parts.join(" OR "))
-        }
-    } else {
-        String::new()
-    };
+    Ok(annotations)
+}
 
-    // Get total count for pagination
-    let total_records: i64 = conn
+#[tauri::command]
+async fn save_table_preferences(
+    state: State<'_, AppState>,
+    db_path: String,
+    table_name: String,
+    preferences: serde_json::Value,
+) -> Result<(), String> {
+    let conn = get_metadata_conn(&state)?;
+    // The frontend owns the shape of this blob, we only store it
+    let preferences_json = serde_json::to_string(&preferences).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO table_preferences (db_path, table_name, preferences_json) VALUES (?1, ?2, ?3)",
+        params![db_path, table_name, preferences_json],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_table_preferences(
+    state: State<'_, AppState>,
+    db_path: String,
+    table_name: String,
+) -> Result<Option<serde_json::Value>, String> {
+    let conn = get_metadata_conn(&state)?;
+    let preferences_json: Option<String> = conn
+        .query_row(
+            "SELECT preferences_json FROM table_preferences WHERE db_path = ?1 AND table_name = ?2",
+            params![db_path, table_name],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    match preferences_json {
+        Some(json) => serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| e.to_string()),
+        None => Ok(None),
+    }
+}
+
+fn load_preferences_json(
+    conn: &Connection,
+) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let json: Option<String> = conn
         .query_row(
-            &format!("SELECT COUNT(*) FROM \"{}\" {}", table, where_clause),
+            "SELECT preferences_json FROM preferences WHERE id = 1",
             [],
             |row| row.get(0),
         )
+        .optional()
         .map_err(|e| e.to_string())?;
 
-    let total_pages = if page_size > 0 {
-        (total_records + page_size - 1) / page_size
-    } else {
-        0
-    };
-    let offset = (page - 1) * page_size;
+    match json {
+        Some(json) => serde_json::from_str(&json).map_err(|e| e.to_string()),
+        None => Ok(serde_json::Map::new()),
+    }
+}
 
-    // Fetch rows
-    let query = format!(
-        "SELECT * FROM \"{}\" {} LIMIT {} OFFSET {}",
-        table, where_clause, page_size, offset
-    );
-    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
-    let col_count = stmt.column_count();
+fn load_app_preferences(conn: &Connection) -> Result<AppPreferences, String> {
+    let json = load_preferences_json(conn)?;
+    serde_json::from_value(serde_json::Value::Object(json)).map_err(|e| e.to_string())
+}
 
-    let rows_iter = stmt
-        .query_map([], |row| {
-            let mut row_values = Vec::new();
-            for i in 0..col_count {
-                let val: rusqlite::types::Value = row.get(i)?;
-                let json_val = match val {
-                    rusqlite::types::Value::Null => serde_json::Value::Null,
-                    rusqlite::types::Value::Integer(i) => serde_json::Value::Number(i.into()),
-                    rusqlite::types::Value::Real(f) => serde_json::Number::from_f64(f)
-                        .map(serde_json::Value::Number)
-                        .unwrap_or(serde_json::Value::Null),
-                    rusqlite::types::Value::Text(t) => serde_json::Value::String(t),
-                    rusqlite::types::Value::Blob(b) => {
-                        serde_json::Value::String(format!("<{} bytes>", b.len()))
-                    }
-                };
-                row_values.push(json_val);
-            }
-            Ok(row_values)
-        })
-        .map_err(|e| e.to_string())?;
+#[tauri::command]
+async fn set_preference(
+    state: State<'_, AppState>,
+    key: String,
+    value: serde_json::Value,
+) -> Result<(), String> {
+    let conn = get_metadata_conn(&state)?;
+    let mut json = load_preferences_json(&conn)?;
+    json.insert(key.clone(), value);
 
-    let mut rows = Vec::new();
-    for row in rows_iter {
-        rows.push(row.map_err(|e| e.to_string())?);
+    // Reject values that don't fit the typed preferences, e.g. a string chunk size
+    let json = serde_json::Value::Object(json);
+    let prefs = serde_json::from_value::<AppPreferences>(json.clone())
+        .map_err(|e| format!("Invalid value for preference '{}': {}", key, e))?;
+    validate_app_preferences(&prefs)?;
+
+    conn.execute(
+        "INSERT OR REPLACE INTO preferences (id, preferences_json) VALUES (1, ?1)",
+        params![json.to_string()],
+    )
+    .map_err(|e| e.to_string())?;
+    *state.preferences.lock().unwrap_or_else(|e| e.into_inner()) = prefs;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_app_preferences(state: State<'_, AppState>) -> Result<AppPreferences, String> {
+    Ok(state
+        .preferences
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone())
+}
+
+// Shared by set_preference and set_app_preferences so neither can store
+// values the other would reject
+fn validate_app_preferences(prefs: &AppPreferences) -> Result<(), String> {
+    if !["system", "light", "dark"].contains(&prefs.theme.as_str()) {
+        return Err(format!(
+            "Unsupported theme '{}', expected system, light or dark",
+            prefs.theme
+        ));
+    }
+    if !["asc", "desc"].contains(&prefs.default_sort_direction.as_str()) {
+        return Err(format!(
+            "Unsupported sort direction '{}', expected asc or desc",
+            prefs.default_sort_direction
+        ));
     }
+    if prefs.default_page_size < 1 || prefs.max_page_size < 1 || prefs.analysis_chunk_size < 1 {
+        return Err("Page and chunk sizes must be at least 1".into());
+    }
+    if prefs.analysis_max_parallelism < 1 {
+        return Err("Analysis parallelism must be at least 1".into());
+    }
+    Ok(())
+}
 
-    Ok(TableData {
-        columns,
-        rows,
-        total_pages,
-    })
+#[tauri::command]
+async fn set_app_preferences(
+    state: State<'_, AppState>,
+    prefs: AppPreferences,
+) -> Result<(), String> {
+    validate_app_preferences(&prefs)?;
+
+    // Merge over the stored JSON so keys set through set_preference survive
+    let conn = get_metadata_conn(&state)?;
+    let mut json = load_preferences_json(&conn)?;
+    if let serde_json::Value::Object(typed) =
+        serde_json::to_value(&prefs).map_err(|e| e.to_string())?
+    {
+        json.extend(typed);
+    }
+
+    conn.execute(
+        "INSERT OR REPLACE INTO preferences (id, preferences_json) VALUES (1, ?1)",
+        params![serde_json::Value::Object(json).to_string()],
+    )
+    .map_err(|e| e.to_string())?;
+    *state.preferences.lock().unwrap_or_else(|e| e.into_inner()) = prefs;
+    Ok(())
 }
 
 #[tauri::command]
-async fn get_db_stats(path: String) -> Result<DbStats, String> {
-    let conn = Connection::open(&path).map_err(|e| e.to_string())?;
+async fn get_preference(
+    state: State<'_, AppState>,
+    key: String,
+) -> Result<Option<serde_json::Value>, String> {
+    let conn = get_metadata_conn(&state)?;
+    if let Some(value) = load_preferences_json(&conn)?.remove(&key) {
+        return Ok(Some(value));
+    }
+
+    // Fall back to the built-in default for known preferences
+    let defaults = serde_json::to_value(AppPreferences::default()).map_err(|e| e.to_string())?;
+    Ok(defaults.get(&key).cloned())
+}
 
+#[tauri::command]
+async fn list_recent_searches(
+    state: State<'_, AppState>,
+    db_path: String,
+    table_name: String,
+    limit: Option<i64>,
+) -> Result<Vec<SearchHistoryEntry>, String> {
+    let conn = get_metadata_conn(&state)?;
+    // Repeated searches collapse into their most recent use
     let mut stmt = conn
-        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%'")
+        .prepare("SELECT MAX(id), db_path, table_name, search_text, searched_at FROM search_history WHERE db_path = ?1 AND table_name = ?2 GROUP BY search_text ORDER BY MAX(id) DESC LIMIT ?3")
         .map_err(|e| e.to_string())?;
-    let table_names: Vec<String> = stmt
-        .query_map([], |row| row.get(0))
+    let entries = stmt
+        .query_map(params![db_path, table_name, limit.unwrap_or(10)], |row| {
+            Ok(SearchHistoryEntry {
+                id: row.get(0)?,
+                db_path: row.get(1)?,
+                table_name: row.get(2)?,
+                search_text: row.get(3)?,
+                searched_at: row.get(4)?,
+            })
+        })
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())?;
 
-    let mut total_records = 0;
-    for table in &table_names {
-        let count: i64 = conn
-            .query_row(&format!("SELECT COUNT(*) FROM \"{}\"", table), [], |row| {
-                row.get(0)
-            })
-            .unwrap_or(0);
-        total_records += count;
-    }
-
-    let file_metadata = std::fs::metadata(&path).map_err(|e| e.to_string())?;
-    let file_size_kb = file_metadata.len() / 1024;
-
-    Ok(DbStats {
-        total_tables: table_names.len(),
-        total_records,
-        file_size_kb,
-    })
+    Ok(entries)
 }
 
 #[tauri::command]
-async fn delete_database(state: State<'_, AppState>, id: i32) -> Result<(), String> {
+async fn clear_search_history(
+    state: State<'_, AppState>,
+    db_path: Option<String>,
+) -> Result<u64, String> {
     let conn = get_metadata_conn(&state)?;
-    conn.execute("DELETE FROM metadata WHERE id = ?1", params![id])
+    let deleted = conn
+        .execute(
+            "DELETE FROM search_history WHERE ?1 IS NULL OR db_path = ?1",
+            params![db_path],
+        )
         .map_err(|e| e.to_string())?;
-    Ok(())
+    Ok(deleted as u64)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -546,51 +7687,511 @@ pub fn run() {
             let conn =
                 Connection::open(&metadata_db_path).expect("Failed to open metadata database");
 
-            // Create table if not exists
-            conn.execute(
-                "CREATE TABLE IF NOT EXISTS metadata (
-                    id INTEGER PRIMARY KEY AUTOINCREMENT,
-                    name TEXT NOT NULL,
-                    path TEXT NOT NULL UNIQUE,
-                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                    last_accessed DATETIME DEFAULT CURRENT_TIMESTAMP,
-                    analysis_results TEXT
-                )",
-                [],
-            )
-            .expect("Failed to create metadata table");
-
-            // Migration: Add analysis_results column if it doesn't exist
-            let mut stmt = conn
-                .prepare("PRAGMA table_info(metadata)")
-                .expect("Failed to get table info");
-            let columns: Vec<String> = stmt
-                .query_map([], |row| row.get(1))
-                .unwrap()
-                .collect::<Result<Vec<_>, _>>()
-                .unwrap();
-
-            if !columns.contains(&"analysis_results".to_string()) {
-                let _ = conn.execute("ALTER TABLE metadata ADD COLUMN analysis_results TEXT", []);
-            }
+            migrate_metadata_db(&conn).expect("Failed to initialize metadata database");
+            let preferences = load_app_preferences(&conn).unwrap_or_default();
+            let _ = conn.execute(
+                "INSERT OR IGNORE INTO version_history (version) VALUES (?1)",
+                params![env!("CARGO_PKG_VERSION")],
+            );
 
             app.manage(AppState {
                 metadata_db_path,
                 analysis_tasks: Mutex::new(HashMap::new()),
+                wal_checkpoints: Mutex::new(HashMap::new()),
+                scheduled_analyses: Mutex::new(HashMap::new()),
+                preferences: Mutex::new(preferences),
+                row_streams: Mutex::new(HashMap::new()),
+                active_queries: Mutex::new(HashMap::new()),
             });
+
+            // Resume the schedules persisted by schedule_analysis
+            if let Ok(schedules) = load_scheduled_analyses(&conn) {
+                for schedule in schedules {
+                    spawn_scheduled_analysis(
+                        app.handle().clone(),
+                        schedule.db_path,
+                        schedule.interval_hours,
+                    );
+                }
+            }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             import_database,
+            import_database_batch,
             list_databases,
+            list_databases_filtered,
+            check_database_files,
             get_tables,
+            invalidate_row_count_cache,
+            stream_table_rows,
+            cancel_stream,
             get_table_data,
+            cancel_query,
+            compute_cross_table_join_preview,
+            get_grouped_table_data,
+            get_pivot_data,
+            search_across_all_tables,
+            find_columns_by_type,
+            diff_table_data,
             get_db_stats,
+            get_encoding,
+            set_journal_mode,
+            get_wal_info,
+            checkpoint_wal,
+            log_checkpoint,
+            list_checkpoint_stats,
+            copy_table,
+            import_json_array_to_table,
+            list_virtual_tables,
+            export_table_to_parquet,
+            export_table_to_excel,
+            export_table_to_ndjson,
+            full_text_search,
+            fts_snippet,
+            analyze_table,
+            validate_sql,
+            get_analyze_statistics,
+            get_index_usage_stats,
+            get_auto_increment_state,
+            get_rowid_range,
+            get_row_by_rowid,
+            copy_row,
+            find_text_encoding_issues,
+            reindex_table,
+            reindex_index,
+            get_function_list,
+            generate_insert_statements,
+            get_full_schema_ddl,
+            get_migration_hints,
+            set_connection_pragmas,
+            detect_database_dialect_hints,
+            find_orphaned_rows,
+            get_column_histogram,
+            compute_column_correlation,
+            compute_column_entropy,
+            detect_date_range,
+            generate_test_data,
+            convert_column_type,
+            get_expression_indexes,
+            merge_databases,
+            describe_table,
+            check_database_update_capability,
+            get_connection_status,
+            get_write_lock_status,
+            get_trigger_details,
+            get_table_constraints,
+            estimate_query_cost,
+            compute_referential_integrity_summary,
+            get_change_log_support,
             delete_database,
+            restore_database,
+            set_database_icon,
+            set_database_notes,
+            get_database_notes,
+            purge_deleted_databases,
+            get_app_data_usage,
+            shrink_metadata_db,
+            get_app_version_history,
             start_db_analysis,
             stop_db_analysis,
+            recover_state,
+            schedule_analysis,
+            cancel_scheduled_analysis,
+            list_scheduled_analyses,
+            export_metadata_backup,
+            export_metadata_as_csv,
+            export_database_metadata_as_json,
+            import_metadata_from_json,
+            export_analysis_report_html,
+            restore_metadata_backup,
+            save_table_preferences,
+            get_table_preferences,
+            annotate_column,
+            get_column_annotations,
+            set_preference,
+            get_preference,
+            get_app_preferences,
+            set_app_preferences,
+            list_recent_searches,
+            clear_search_history,
             version::versionno
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_identifier_escapes_names() {
+        assert_eq!(quote_identifier("users"), "\"users\"");
+        assert_eq!(
+            quote_identifier("my \"odd\" table"),
+            "\"my \"\"odd\"\" table\""
+        );
+        assert_eq!(quote_identifier("`ticks`"), "\"`ticks`\"");
+        assert_eq!(quote_identifier("with spaces"), "\"with spaces\"");
+        assert_eq!(quote_identifier("données_表"), "\"données_表\"");
+    }
+
+    #[test]
+    fn quote_identifier_round_trips_through_sqlite() {
+        let conn = Connection::open_in_memory().unwrap();
+        for name in [
+            "plain",
+            "my \"odd\" table",
+            "`ticks`",
+            "with spaces",
+            "données_表",
+            "x\"; DROP TABLE plain; --",
+        ] {
+            conn.execute_batch(&format!(
+                "CREATE TABLE {0} ({0} TEXT); INSERT INTO {0} VALUES ('ok');",
+                quote_identifier(name)
+            ))
+            .unwrap();
+            let value: String = conn
+                .query_row(
+                    &format!("SELECT {0} FROM {0}", quote_identifier(name)),
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(value, "ok");
+            assert_eq!(get_column_names(&conn, name).unwrap(), vec![name]);
+        }
+        assert!(table_exists(&conn, "plain").unwrap());
+    }
+
+    fn temp_dir(label: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "db-visualizer-{}-{}-{}",
+            label,
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn metadata_paths(conn: &Connection) -> Vec<String> {
+        conn.prepare("SELECT path FROM metadata")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn import_through_symlink_reuses_canonical_row() {
+        let dir = temp_dir("symlink");
+        let db_path = dir.join("real.db");
+        Connection::open(&db_path)
+            .unwrap()
+            .execute_batch("CREATE TABLE t (x)")
+            .unwrap();
+        let link_path = dir.join("link.db");
+        std::os::unix::fs::symlink(&db_path, &link_path).unwrap();
+        let canonical = canonical_db_path(db_path.to_str().unwrap()).unwrap();
+
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_metadata_db(&conn).unwrap();
+        let first = insert_database_metadata(&conn, "real", db_path.to_str().unwrap(), false, None)
+            .unwrap();
+        let second =
+            insert_database_metadata(&conn, "link", link_path.to_str().unwrap(), false, None)
+                .unwrap();
+        assert_eq!(first.metadata.path, canonical);
+        assert_eq!(second.metadata.path, canonical);
+        assert_eq!(metadata_paths(&conn), vec![canonical.clone()]);
+
+        // A row stored under the symlink before canonicalization is moved, not duplicated
+        conn.execute("DELETE FROM metadata", []).unwrap();
+        conn.execute(
+            "INSERT INTO metadata (name, path) VALUES ('legacy', ?1)",
+            params![link_path.to_str().unwrap()],
+        )
+        .unwrap();
+        insert_database_metadata(&conn, "real", db_path.to_str().unwrap(), false, None).unwrap();
+        assert_eq!(metadata_paths(&conn), vec![canonical]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reimport_keeps_row_id_and_user_data() {
+        let dir = temp_dir("reimport");
+        let db_path = dir.join("data.db");
+        Connection::open(&db_path)
+            .unwrap()
+            .execute_batch("CREATE TABLE t (x)")
+            .unwrap();
+        let path = db_path.to_str().unwrap();
+
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_metadata_db(&conn).unwrap();
+        let first = insert_database_metadata(&conn, "data", path, false, Some("📦")).unwrap();
+        conn.execute(
+            "UPDATE metadata SET notes = 'keep me', analysis_results = '{}', deleted_at = CURRENT_TIMESTAMP",
+            [],
+        )
+        .unwrap();
+
+        let second = insert_database_metadata(&conn, "renamed", path, false, None).unwrap();
+        assert_eq!(second.metadata.id, first.metadata.id);
+        assert_eq!(second.metadata.name, "renamed");
+        assert_eq!(second.metadata.icon.as_deref(), Some("📦"));
+        assert_eq!(second.metadata.notes.as_deref(), Some("keep me"));
+        assert_eq!(second.metadata.analysis_results.as_deref(), Some("{}"));
+        let deleted_at: Option<String> = conn
+            .query_row("SELECT deleted_at FROM metadata", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(deleted_at, None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn encrypted_import_is_refused_without_saving() {
+        let dir = temp_dir("encrypted");
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_metadata_db(&conn).unwrap();
+
+        // xorshift output stands in for an encrypted file's pages
+        let mut x: u32 = 0x9E37_79B9;
+        let bytes: Vec<u8> = (0..4096)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                (x >> 24) as u8
+            })
+            .collect();
+        let encrypted_path = dir.join("secret.db");
+        std::fs::write(&encrypted_path, bytes).unwrap();
+        let err = insert_database_metadata(
+            &conn,
+            "secret",
+            encrypted_path.to_str().unwrap(),
+            false,
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(err.reason, "encrypted");
+
+        let text_path = dir.join("notes.txt");
+        std::fs::write(&text_path, "not a database!\n".repeat(256)).unwrap();
+        let err =
+            insert_database_metadata(&conn, "notes", text_path.to_str().unwrap(), false, None)
+                .unwrap_err();
+        assert_eq!(err.reason, "invalid");
+
+        assert!(metadata_paths(&conn).is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn out_of_range_schedule_interval_has_no_period() {
+        assert!(schedule_period(MAX_SCHEDULE_INTERVAL_HOURS).is_some());
+        assert!(schedule_period(u64::MAX / 1000).is_none());
+        assert!(schedule_period(u64::MAX).is_none());
+    }
+
+    #[test]
+    fn coerce_value_refuses_lossy_numbers() {
+        use rusqlite::types::Value;
+        assert_eq!(
+            coerce_value(Value::Real(42.0), "INTEGER"),
+            Some(Value::Integer(42))
+        );
+        assert_eq!(coerce_value(Value::Real(1e300), "INTEGER"), None);
+        assert_eq!(coerce_value(Value::Real(-1e300), "INTEGER"), None);
+        assert_eq!(coerce_value(Value::Real(i64::MAX as f64), "INTEGER"), None);
+        assert_eq!(
+            coerce_value(Value::Integer(1 << 53), "REAL"),
+            Some(Value::Real((1u64 << 53) as f64))
+        );
+        assert_eq!(coerce_value(Value::Integer((1 << 53) + 1), "REAL"), None);
+        assert_eq!(coerce_value(Value::Integer(i64::MAX), "REAL"), None);
+    }
+
+    #[test]
+    fn sql_literal_round_trips_special_reals() {
+        use rusqlite::types::Value;
+        let conn = Connection::open_in_memory().unwrap();
+        for f in [f64::INFINITY, f64::NEG_INFINITY, 1.5, -0.25] {
+            let literal = sql_literal(&Value::Real(f));
+            let back: f64 = conn
+                .query_row(&format!("SELECT {}", literal), [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(back, f);
+        }
+        assert_eq!(sql_literal(&Value::Real(f64::NAN)), "NULL");
+    }
+
+    #[test]
+    fn function_list_reads_the_pragma() {
+        let conn = Connection::open_in_memory().unwrap();
+        let functions = read_function_list(&conn).unwrap();
+        assert!(functions.iter().any(|f| f.name == "abs" && f.builtin));
+    }
+
+    #[test]
+    fn import_of_missing_path_is_rejected() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_metadata_db(&conn).unwrap();
+        let dir = temp_dir("missing");
+        let missing = dir.join("nope.db");
+        let err = insert_database_metadata(&conn, "nope", missing.to_str().unwrap(), false, None)
+            .unwrap_err();
+        assert_eq!(err.reason, "not_found");
+        assert!(metadata_paths(&conn).is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn connection_prefs_apply_to_any_spelling_of_the_path() {
+        let dir = temp_dir("prefs");
+        let db_path = dir.join("prefs.db");
+        Connection::open(&db_path).unwrap();
+        let prefs_conn = Connection::open_in_memory().unwrap();
+        migrate_metadata_db(&prefs_conn).unwrap();
+        prefs_conn
+            .execute(
+                "INSERT INTO connection_preferences (db_path, cache_size_kb) VALUES (?1, 4096)",
+                params![canonical_db_path(db_path.to_str().unwrap()).unwrap()],
+            )
+            .unwrap();
+
+        let other_spelling = dir
+            .join("..")
+            .join(dir.file_name().unwrap())
+            .join("prefs.db");
+        let conn = open_database_with_prefs(
+            &prefs_conn,
+            other_spelling.to_str().unwrap(),
+            OpenFlags::default(),
+        )
+        .unwrap();
+        let cache_size: i64 = conn
+            .query_row("PRAGMA cache_size", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(cache_size, -4096);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn apply_pragmas_handles_extreme_values() {
+        let conn = Connection::open_in_memory().unwrap();
+        apply_pragmas(&conn, Some(i64::MIN), Some(i64::MAX), Some(i64::MIN)).unwrap();
+    }
+
+    // Runs the get_table_data search condition and returns the matching ids
+    fn search_ids(conn: &Connection, search: &str) -> Vec<i64> {
+        let columns = vec!["id".to_string(), "body".to_string()];
+        let (condition, search_params) = table_search_condition(&columns, search);
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT id FROM items WHERE {} ORDER BY id",
+                condition
+            ))
+            .unwrap();
+        stmt.query_map(params_from_iter(&search_params), |row| row.get(0))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn table_search_condition_keeps_wildcards() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE items (id INTEGER PRIMARY KEY, body TEXT);
+             INSERT INTO items (body) VALUES ('abc'), ('a_c'), ('50% off'), ('none');",
+        )
+        .unwrap();
+        assert_eq!(search_ids(&conn, "a_c"), vec![1, 2]);
+        assert_eq!(search_ids(&conn, "a%c"), vec![1, 2]);
+        assert_eq!(search_ids(&conn, "50%"), vec![3]);
+        assert_eq!(search_ids(&conn, "ABC"), vec![1]);
+    }
+
+    #[test]
+    fn table_search_condition_resists_injection() {
+        const ALPHABET: &[&str] = &[
+            "'",
+            "\"",
+            "`",
+            ";",
+            "--",
+            "/*",
+            "*/",
+            "\\",
+            "(",
+            ")",
+            "%",
+            "_",
+            "[",
+            "]",
+            " ",
+            "a",
+            "OR",
+            "1=1",
+            "DROP TABLE items",
+            "é",
+            "表",
+        ];
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE items (id INTEGER PRIMARY KEY, body TEXT)")
+            .unwrap();
+        for body in ["plain", "it's", "a \"quoted\" word", "semi;colon"] {
+            conn.execute("INSERT INTO items (body) VALUES (?1)", params![body])
+                .unwrap();
+        }
+
+        // Deterministic pseudo-random payloads built from SQL metacharacters
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..2000 {
+            let mut needle = String::new();
+            for _ in 0..(seed % 8 + 1) {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                needle.push_str(ALPHABET[(seed >> 33) as usize % ALPHABET.len()]);
+            }
+            // Without LIKE wildcards a match is exactly a substring match
+            if !needle.contains(['%', '_']) {
+                let expected: Vec<i64> = conn
+                    .prepare("SELECT id, body FROM items ORDER BY id")
+                    .unwrap()
+                    .query_map([], |row| {
+                        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+                    })
+                    .unwrap()
+                    .filter_map(|row| {
+                        let (id, body) = row.unwrap();
+                        (body.to_lowercase().contains(&needle.to_lowercase())
+                            || id.to_string().contains(&needle))
+                        .then_some(id)
+                    })
+                    .collect();
+                assert_eq!(search_ids(&conn, &needle), expected, "needle {:?}", needle);
+            } else {
+                search_ids(&conn, &needle);
+            }
+        }
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 4);
+    }
+}
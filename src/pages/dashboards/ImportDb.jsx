@@ -44,10 +44,17 @@ const ImportDb = () => {
     setError(null);
 
     try {
-      await invoke('import_database', { name: dbName, path: dbPath });
+      const result = await invoke('import_database', { name: dbName, path: dbPath });
+      if (result.is_duplicate) {
+        const proceed = window.confirm(
+          `This file looks like "${result.metadata.name}", which is already imported. Import it anyway?`
+        );
+        if (!proceed) return;
+        await invoke('import_database', { name: dbName, path: dbPath, allowDuplicate: true });
+      }
       navigate('/');
     } catch (err) {
-      setError(err.toString());
+      setError(err?.message ?? err.toString());
     } finally {
       setLoading(false);
     }
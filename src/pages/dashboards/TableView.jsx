@@ -66,9 +66,11 @@ const TableView = () => {
       const result = await invoke('get_table_data', {
         path: dbPath,
         table: selectedTable,
-        page,
-        pageSize,
-        search: debouncedSearch || null
+        query: {
+          page,
+          page_size: pageSize,
+          search: debouncedSearch || null
+        }
       });
       setColumns(result.columns);
       setRows(result.rows);